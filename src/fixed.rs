@@ -0,0 +1,115 @@
+//! # Fixed
+//!
+//! Aritmética de ponto fixo 16.16 (sem float) para animações, escala e
+//! gradientes.
+//!
+//! Produtos e quocientes usam `i64` intermediário. Conversões para inteiro
+//! arredondam para baixo (`to_int`) ou para o mais próximo (`round`).
+
+use core::ops::{Add, Sub};
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Bits fracionários.
+pub const FRAC_BITS: u32 = 16;
+
+// =============================================================================
+// FIXED
+// =============================================================================
+
+/// Número em ponto fixo 16.16.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Fixed(pub i32);
+
+// TODO: Revisar no futuro
+#[allow(unused)]
+impl Fixed {
+    /// 0.0
+    pub const ZERO: Self = Self(0);
+    /// 1.0
+    pub const ONE: Self = Self(1 << FRAC_BITS);
+    /// 0.5
+    pub const HALF: Self = Self(1 << (FRAC_BITS - 1));
+
+    /// Converte inteiro.
+    #[inline]
+    pub const fn from_int(value: i32) -> Self {
+        Self(value << FRAC_BITS)
+    }
+
+    /// `num / den` (arredondado para baixo). `den == 0` retorna 0.
+    #[inline]
+    pub const fn from_ratio(num: i64, den: i64) -> Self {
+        if den == 0 {
+            return Self::ZERO;
+        }
+        Self(((num << FRAC_BITS) / den) as i32)
+    }
+
+    /// Parte inteira (arredondada para baixo, inclusive para negativos).
+    #[inline]
+    pub const fn to_int(self) -> i32 {
+        self.0 >> FRAC_BITS
+    }
+
+    /// Inteiro mais próximo (meio arredonda para cima).
+    #[inline]
+    pub const fn round(self) -> i32 {
+        (self.0 + Self::HALF.0) >> FRAC_BITS
+    }
+
+    /// Limita a `[min, max]`.
+    #[inline]
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        Self(self.0.clamp(min.0, max.0))
+    }
+
+    /// Produto (truncado para baixo).
+    #[inline]
+    pub const fn mul(self, other: Self) -> Self {
+        Self(((self.0 as i64 * other.0 as i64) >> FRAC_BITS) as i32)
+    }
+
+    /// Quociente. Divisão por zero retorna 0.
+    #[inline]
+    pub const fn div(self, other: Self) -> Self {
+        if other.0 == 0 {
+            return Self::ZERO;
+        }
+        Self((((self.0 as i64) << FRAC_BITS) / other.0 as i64) as i32)
+    }
+
+    /// Multiplica um inteiro e retorna inteiro (arredondado para baixo).
+    ///
+    /// Usa `i64`, então não satura mesmo quando `n * self` não cabe em 16.16.
+    #[inline]
+    pub const fn mul_int(self, n: i64) -> i64 {
+        (n * self.0 as i64) >> FRAC_BITS
+    }
+
+    /// Interpola inteiros: `a` em `t = 0`, `b` em `t = ONE` (exatos nas pontas).
+    #[inline]
+    pub const fn lerp(a: i64, b: i64, t: Self) -> i64 {
+        a + (((b - a) * t.0 as i64) >> FRAC_BITS)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
@@ -0,0 +1,62 @@
+//! # Keymap
+//!
+//! Tradução de scancode (PS/2 set 1) + modificadores para caractere.
+//!
+//! Teclas sem caractere (setas, F1..F12, modificadores) retornam `None`.
+
+// =============================================================================
+// LAYOUTS
+// =============================================================================
+
+/// Layout de teclado ativo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Keymap {
+    /// US QWERTY.
+    #[default]
+    UsQwerty,
+}
+
+impl Keymap {
+    /// Traduz um scancode para caractere, considerando Shift.
+    pub fn translate(self, scancode: u32, shift: bool) -> Option<char> {
+        let (normal, shifted) = match self {
+            Self::UsQwerty => (&US_QWERTY, &US_QWERTY_SHIFT),
+        };
+
+        let table = if shift { shifted } else { normal };
+        match table.get(scancode as usize) {
+            Some(&0) | None => None,
+            Some(&byte) => Some(byte as char),
+        }
+    }
+}
+
+// =============================================================================
+// TABELAS
+// =============================================================================
+
+/// US QWERTY sem modificadores (índice = scancode, 0 = sem caractere).
+#[rustfmt::skip]
+static US_QWERTY: [u8; 0x3A] = [
+    // 0x00
+    0, 0, b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'0', b'-', b'=', 0x08, b'\t',
+    // 0x10
+    b'q', b'w', b'e', b'r', b't', b'y', b'u', b'i', b'o', b'p', b'[', b']', b'\n', 0, b'a', b's',
+    // 0x20
+    b'd', b'f', b'g', b'h', b'j', b'k', b'l', b';', b'\'', b'`', 0, b'\\', b'z', b'x', b'c', b'v',
+    // 0x30
+    b'b', b'n', b'm', b',', b'.', b'/', 0, b'*', 0, b' ',
+];
+
+/// US QWERTY com Shift.
+#[rustfmt::skip]
+static US_QWERTY_SHIFT: [u8; 0x3A] = [
+    // 0x00
+    0, 0, b'!', b'@', b'#', b'$', b'%', b'^', b'&', b'*', b'(', b')', b'_', b'+', 0x08, b'\t',
+    // 0x10
+    b'Q', b'W', b'E', b'R', b'T', b'Y', b'U', b'I', b'O', b'P', b'{', b'}', b'\n', 0, b'A', b'S',
+    // 0x20
+    b'D', b'F', b'G', b'H', b'J', b'K', b'L', b':', b'"', b'~', 0, b'|', b'Z', b'X', b'C', b'V',
+    // 0x30
+    b'B', b'N', b'M', b'<', b'>', b'?', 0, b'*', 0, b' ',
+];
@@ -1,9 +1,25 @@
 //! # Input Manager
 //!
 //! Gerenciador centralizado de entrada (mouse, teclado).
+//!
+//! Guarda o **ponteiro lógico** (posição e botões usados para hit-test,
+//! arrasto e cursor). Mouse e toque emulando mouse movem o mesmo ponteiro e
+//! compartilham o estado de botões.
+//!
+//! Teclas não são armazenadas aqui: o servidor as despacha direto para a
+//! janela focada.
 
 use gfx_types::geometry::Point;
-use redpowder::input::{KeyCode, MouseButton, MouseState};
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Máscara do botão esquerdo no campo `buttons` dos eventos.
+const LEFT_BUTTON: u32 = 0x01;
+
+/// Máscara do botão direito no campo `buttons` dos eventos.
+const RIGHT_BUTTON: u32 = 0x02;
 
 // =============================================================================
 // INPUT MANAGER
@@ -11,102 +27,81 @@ use redpowder::input::{KeyCode, MouseButton, MouseState};
 
 /// Gerenciador centralizado de entrada.
 pub struct InputManager {
-    /// Estado atual do mouse.
-    pub mouse: MouseState,
-    /// Posição do mouse.
-    pub mouse_pos: Point,
-    /// Última tecla pressionada.
-    pub last_key: Option<(KeyCode, bool)>,
-    /// Botões de mouse pressionados no frame anterior.
-    pub prev_buttons: u8,
+    /// Posição do ponteiro lógico.
+    pub pointer: Point,
+    /// Botões do ponteiro lógico no evento anterior.
+    prev_buttons: u32,
+    /// Último movimento do mouse ainda não processado neste frame.
+    pending_motion: Option<Point>,
 }
 
 impl InputManager {
     /// Cria novo gerenciador.
     pub fn new() -> Self {
         Self {
-            mouse: MouseState::default(),
-            mouse_pos: Point::ZERO,
-            last_key: None,
+            pointer: Point::ZERO,
             prev_buttons: 0,
+            pending_motion: None,
         }
     }
 
-    /// Atualiza estado do mouse.
-    pub fn update_mouse(&mut self, x: i32, y: i32, buttons: u8) {
-        self.prev_buttons = self.mouse.buttons;
-        self.mouse.x = x;
-        self.mouse.y = y;
-        self.mouse.buttons = buttons;
-        self.mouse_pos = Point::new(x, y);
-    }
+    // =========================================================================
+    // PONTEIRO LÓGICO
+    // =========================================================================
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Atualiza estado do teclado.
-    pub fn update_keyboard(&mut self, keycode: KeyCode, pressed: bool) {
-        self.last_key = Some((keycode, pressed));
+    /// Move o ponteiro lógico (mouse ou toque).
+    pub fn move_pointer(&mut self, x: i32, y: i32) {
+        self.pointer = Point::new(x, y);
     }
 
-    /// Atualiza a partir de um evento do serviço de input.
-    pub fn update_from_service(
-        &mut self,
-        event_type: u32,
-        key_code: u32,
-        pressed: u32,
-        x: i32,
-        y: i32,
-        buttons: u32,
-    ) {
-        match event_type {
-            1 => {
-                // Evento de teclado
-                let code = KeyCode::from_scancode(key_code as u8);
-                self.last_key = Some((code, pressed == 1));
-            }
-            2 => {
-                // Evento de mouse
-                self.update_mouse(x, y, buttons as u8);
-            }
-            _ => {}
+    /// Adia um evento de mouse só de movimento (botões iguais aos do evento
+    /// anterior), guardando apenas a posição mais recente. Retorna false se
+    /// o evento muda algum botão e deve ser processado na hora.
+    ///
+    /// Mouses com alta taxa de amostragem mandam centenas de movimentos por
+    /// frame; assim o hit-test e o despacho rodam uma vez por frame.
+    pub fn coalesce_motion(&mut self, x: i32, y: i32, buttons: u32) -> bool {
+        if buttons != self.prev_buttons {
+            return false;
         }
+        self.pending_motion = Some(Point::new(x, y));
+        true
+    }
+
+    /// Retira o movimento adiado, se houver.
+    pub fn take_pending_motion(&mut self) -> Option<Point> {
+        self.pending_motion.take()
+    }
+
+    /// Botões do ponteiro no último evento processado.
+    #[inline]
+    pub fn buttons(&self) -> u32 {
+        self.prev_buttons
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Verifica se botão foi pressionado neste frame.
-    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
-        let mask = button.mask();
-        (self.mouse.buttons & mask) != 0 && (self.prev_buttons & mask) == 0
+    /// Guarda os botões do ponteiro para a detecção de borda do próximo evento.
+    pub fn save_buttons(&mut self, buttons: u32) {
+        self.prev_buttons = buttons;
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Verifica se botão foi solto neste frame.
-    pub fn button_just_released(&self, button: MouseButton) -> bool {
-        let mask = button.mask();
-        (self.mouse.buttons & mask) == 0 && (self.prev_buttons & mask) != 0
+    /// Retorna true se botão esquerdo foi pressionado neste evento.
+    pub fn left_just_pressed(&self, current_buttons: u32) -> bool {
+        (current_buttons & LEFT_BUTTON) != 0 && (self.prev_buttons & LEFT_BUTTON) == 0
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Verifica se botão está pressionado.
-    pub fn button_pressed(&self, button: MouseButton) -> bool {
-        self.mouse.is_pressed(button)
+    /// Retorna true se botão esquerdo foi solto neste evento.
+    pub fn left_just_released(&self, current_buttons: u32) -> bool {
+        (current_buttons & LEFT_BUTTON) == 0 && (self.prev_buttons & LEFT_BUTTON) != 0
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Retorna a última tecla pressionada e limpa.
-    pub fn take_key(&mut self) -> Option<(KeyCode, bool)> {
-        self.last_key.take()
+    /// Retorna true se botão direito foi pressionado neste evento.
+    pub fn right_just_pressed(&self, current_buttons: u32) -> bool {
+        (current_buttons & RIGHT_BUTTON) != 0 && (self.prev_buttons & RIGHT_BUTTON) == 0
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Limpa estado da tecla.
-    pub fn clear_key(&mut self) {
-        self.last_key = None;
+    /// Retorna true se botão esquerdo está pressionado.
+    pub fn left_pressed(&self, current_buttons: u32) -> bool {
+        (current_buttons & LEFT_BUTTON) != 0
     }
 }
 
@@ -2,6 +2,8 @@
 //!
 //! Gerenciamento de entrada (mouse, teclado).
 
+pub mod keymap;
 mod manager;
+pub mod scancode;
 
 pub use manager::InputManager;
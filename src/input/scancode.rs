@@ -0,0 +1,28 @@
+//! # Scancodes
+//!
+//! Scancodes (PS/2 set 1) usados pelo compositor para atalhos internos.
+//!
+//! Teclas estendidas (setas) chegam do serviço de input sem o prefixo `0xE0`.
+
+/// Escape.
+pub const ESCAPE: u32 = 0x01;
+/// Enter.
+pub const ENTER: u32 = 0x1C;
+/// Ctrl esquerdo.
+pub const LEFT_CTRL: u32 = 0x1D;
+/// Shift esquerdo.
+pub const LEFT_SHIFT: u32 = 0x2A;
+/// Shift direito.
+pub const RIGHT_SHIFT: u32 = 0x36;
+/// Alt esquerdo.
+pub const LEFT_ALT: u32 = 0x38;
+/// F7.
+pub const F7: u32 = 0x41;
+/// Seta para cima.
+pub const UP: u32 = 0x48;
+/// Seta para esquerda.
+pub const LEFT: u32 = 0x4B;
+/// Seta para direita.
+pub const RIGHT: u32 = 0x4D;
+/// Seta para baixo.
+pub const DOWN: u32 = 0x50;
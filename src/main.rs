@@ -31,10 +31,12 @@
 extern crate alloc;
 
 // Módulos internos
+mod fixed;
 mod input;
 mod render;
 mod scene;
 mod server;
+mod shm;
 mod ui;
 
 use core::panic::PanicInfo;
@@ -0,0 +1,172 @@
+//! # Backbuffer
+//!
+//! Buffer de pixels em RAM onde a cena é composta antes de ser
+//! apresentada no display.
+
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use super::blitter::Blitter;
+use crate::scene::{area_checked, clamp_rect};
+
+// =============================================================================
+// BACKBUFFER
+// =============================================================================
+
+/// Buffer de composição (ARGB8888).
+pub struct Backbuffer {
+    /// Pixels em ordem de linhas.
+    pixels: Vec<u32>,
+    /// Dimensões do buffer.
+    size: Size,
+}
+
+impl Backbuffer {
+    /// Cria novo backbuffer preenchido com a cor dada.
+    pub fn new(size: Size, color: Color) -> Self {
+        let count = area_checked(size).unwrap_or(0);
+        Self {
+            pixels: vec![color.as_u32(); count],
+            size,
+        }
+    }
+
+    // =========================================================================
+    // PROPRIEDADES
+    // =========================================================================
+
+    /// Retorna dimensões do buffer.
+    #[inline]
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Retorna os pixels.
+    #[inline]
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Retorna os pixels (mutável).
+    #[inline]
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
+
+    /// Tamanho do buffer em bytes.
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        self.pixels.len() * 4
+    }
+
+    /// Bytes `start..end` no layout do framebuffer (ARGB32 little-endian:
+    /// B, G, R, A na memória), para apresentação.
+    ///
+    /// Em hosts little-endian é o próprio buffer; nos demais os pixels são
+    /// empacotados com `to_le_bytes` em uma cópia.
+    pub fn le_bytes(&self, start: usize, end: usize) -> Cow<'_, [u8]> {
+        let end = end.min(self.byte_len());
+        let start = start.min(end);
+
+        #[cfg(target_endian = "little")]
+        {
+            let bytes = unsafe {
+                core::slice::from_raw_parts(self.pixels.as_ptr() as *const u8, self.byte_len())
+            };
+            Cow::Borrowed(&bytes[start..end])
+        }
+
+        #[cfg(not(target_endian = "little"))]
+        {
+            let first = start / 4;
+            let last = end.div_ceil(4);
+            let mut packed = Vec::with_capacity((last - first) * 4);
+            for pixel in &self.pixels[first..last] {
+                packed.extend_from_slice(&pixel.to_le_bytes());
+            }
+            let skip = start - first * 4;
+            packed.truncate(skip + (end - start));
+            packed.drain(..skip);
+            Cow::Owned(packed)
+        }
+    }
+
+    /// Retorna o pixel em (x, y), ou None se fora do buffer.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<u32> {
+        if x < 0 || y < 0 || x as u32 >= self.size.width || y as u32 >= self.size.height {
+            return None;
+        }
+        self.pixels
+            .get(y as usize * self.size.width as usize + x as usize)
+            .copied()
+    }
+
+    /// Checksum (FNV-1a) dos pixels dentro de `rect`.
+    ///
+    /// Usado para detectar frames cujo conteúdo não mudou apesar do damage.
+    pub fn region_hash(&self, rect: Rect) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let mut hash = FNV_OFFSET;
+        let area = match clamp_rect(rect, Rect::from_size(self.size)) {
+            Some(r) => r,
+            None => return hash,
+        };
+
+        let stride = self.size.width as usize;
+        for y in area.y as usize..area.bottom() as usize {
+            let start = y * stride + area.x as usize;
+            for &pixel in &self.pixels[start..start + area.width as usize] {
+                hash ^= pixel as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    // =========================================================================
+    // LIMPEZA
+    // =========================================================================
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Preenche o buffer inteiro com uma cor sólida.
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.fill(color.as_u32());
+    }
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Limpa o buffer para totalmente transparente (alpha 0).
+    ///
+    /// Usado por buffers de rascunho (ex: overlay) que serão compostos
+    /// sobre o backbuffer principal via [`Backbuffer::blend_onto`]:
+    /// pixels não desenhados são ignorados pelo `blit_alpha`.
+    pub fn clear_transparent(&mut self) {
+        self.pixels.fill(0);
+    }
+
+    // =========================================================================
+    // COMPOSIÇÃO
+    // =========================================================================
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Compõe este buffer sobre `dst` com alpha blending.
+    pub fn blend_onto(&self, dst: &mut Backbuffer, dst_point: Point) {
+        let dst_size = dst.size();
+        Blitter::blit_alpha(
+            &mut dst.pixels,
+            dst_size,
+            &self.pixels,
+            self.size,
+            Rect::from_size(self.size),
+            dst_point,
+            Rect::from_size(dst_size),
+        );
+    }
+}
@@ -15,6 +15,16 @@
 use gfx_types::color::{BlendMode, Color};
 use gfx_types::geometry::{Point, Rect, Size};
 
+use crate::fixed::Fixed;
+use crate::scene::{clamp_rect, clamp_to_screen};
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Pixels do carimbo usado por `fill_rect_fast`.
+const FILL_STAMP_LEN: usize = 64;
+
 // =============================================================================
 // BLITTER
 // =============================================================================
@@ -29,7 +39,8 @@ impl Blitter {
 
     /// Copia região de src para dst (sem alpha, opaco).
     ///
-    /// Copia linha-a-linha para máxima performance.
+    /// Copia linha-a-linha para máxima performance. Só escreve dentro de
+    /// `clip` (coordenadas de `dst`); use a tela inteira para redesenho total.
     #[inline]
     pub fn blit_opaque(
         dst: &mut [u32],
@@ -38,12 +49,15 @@ impl Blitter {
         src_size: Size,
         src_rect: Rect,
         dst_point: Point,
+        clip: Rect,
     ) {
-        // Cálculo de clipping
-        let dst_rect = Rect::new(dst_point.x, dst_point.y, src_rect.width, src_rect.height);
+        if src_rect.is_empty() || is_empty_size(src_size) || is_empty_target(dst, dst_size) {
+            return;
+        }
 
-        let dst_bounds = Rect::new(0, 0, dst_size.width, dst_size.height);
-        let clipped = match dst_rect.intersection(&dst_bounds) {
+        // Cálculo de clipping (destino pode ter origem negativa)
+        let dst_rect = Rect::new(dst_point.x, dst_point.y, src_rect.width, src_rect.height);
+        let clipped = match clip_to_target(dst_rect, dst_size, clip) {
             Some(r) => r,
             None => return,
         };
@@ -82,6 +96,8 @@ impl Blitter {
     // =========================================================================
 
     /// Copia com verificação de alpha (para superfícies transparentes).
+    ///
+    /// Só escreve dentro de `clip` (coordenadas de `dst`).
     #[inline]
     pub fn blit_alpha(
         dst: &mut [u32],
@@ -90,23 +106,59 @@ impl Blitter {
         src_size: Size,
         src_rect: Rect,
         dst_point: Point,
+        clip: Rect,
     ) {
+        Self::blit_alpha_opacity(dst, dst_size, src, src_size, src_rect, dst_point, clip, 255);
+    }
+
+    /// Copia com alpha blending, multiplicando o alpha de cada pixel por
+    /// uma opacidade global (0-255). Só escreve dentro de `clip`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_alpha_opacity(
+        dst: &mut [u32],
+        dst_size: Size,
+        src: &[u32],
+        src_size: Size,
+        src_rect: Rect,
+        dst_point: Point,
+        clip: Rect,
+        opacity: u8,
+    ) {
+        if opacity == 0
+            || src_rect.is_empty()
+            || is_empty_size(src_size)
+            || is_empty_target(dst, dst_size)
+        {
+            return;
+        }
+
+        // Cálculo de clipping (destino pode ter origem negativa)
+        let dst_rect = Rect::new(dst_point.x, dst_point.y, src_rect.width, src_rect.height);
+        let clipped = match clip_to_target(dst_rect, dst_size, clip) {
+            Some(r) => r,
+            None => return,
+        };
+
         let src_stride = src_size.width as usize;
         let dst_stride = dst_size.width as usize;
 
-        for y in 0..src_rect.height as usize {
-            let src_y = src_rect.y as usize + y;
-            let dst_y = dst_point.y as usize + y;
+        let offset_x = (clipped.x - dst_point.x) as usize;
+        let offset_y = (clipped.y - dst_point.y) as usize;
 
-            if src_y >= src_size.height as usize || dst_y >= dst_size.height as usize {
+        for y in 0..clipped.height as usize {
+            let src_y = src_rect.y as usize + offset_y + y;
+            let dst_y = clipped.y as usize + y;
+
+            if src_y >= src_size.height as usize {
                 continue;
             }
 
-            for x in 0..src_rect.width as usize {
-                let src_x = src_rect.x as usize + x;
-                let dst_x = dst_point.x as usize + x;
+            for x in 0..clipped.width as usize {
+                let src_x = src_rect.x as usize + offset_x + x;
+                let dst_x = clipped.x as usize + x;
 
-                if src_x >= src_size.width as usize || dst_x >= dst_size.width as usize {
+                if src_x >= src_size.width as usize {
                     continue;
                 }
 
@@ -117,8 +169,13 @@ impl Blitter {
                     continue;
                 }
 
-                let src_pixel = src[src_idx];
-                let alpha = src_pixel >> 24;
+                let mut src_pixel = src[src_idx];
+                let mut alpha = src_pixel >> 24;
+
+                if opacity < 255 {
+                    alpha = alpha * opacity as u32 / 255;
+                    src_pixel = (alpha << 24) | (src_pixel & 0x00FFFFFF);
+                }
 
                 if alpha == 0xFF {
                     dst[dst_idx] = src_pixel;
@@ -141,40 +198,92 @@ impl Blitter {
         src_size: Size,
         src_rect: Rect,
     ) {
+        Self::blit_scaled_clipped(
+            dst,
+            dst_size,
+            dst_rect,
+            src,
+            src_size,
+            src_rect,
+            Rect::from_size(dst_size),
+        );
+    }
+
+    /// Blit com escala (nearest neighbor), escrevendo apenas dentro de `clip`.
+    ///
+    /// `dst_rect` pode ultrapassar a tela (inclusive com origem negativa);
+    /// o mapeamento de escala é sempre relativo a `dst_rect` inteiro.
+    #[inline]
+    pub fn blit_scaled_clipped(
+        dst: &mut [u32],
+        dst_size: Size,
+        dst_rect: Rect,
+        src: &[u32],
+        src_size: Size,
+        src_rect: Rect,
+        clip: Rect,
+    ) {
+        Self::blit_scaled_opacity(dst, dst_size, dst_rect, src, src_size, src_rect, clip, 255);
+    }
+
+    /// Blit com escala (nearest neighbor), multiplicando o alpha de cada
+    /// pixel por uma opacidade global (0-255). Só escreve dentro de `clip`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_scaled_opacity(
+        dst: &mut [u32],
+        dst_size: Size,
+        dst_rect: Rect,
+        src: &[u32],
+        src_size: Size,
+        src_rect: Rect,
+        clip: Rect,
+        opacity: u8,
+    ) {
+        if opacity == 0
+            || dst_rect.is_empty()
+            || src_rect.is_empty()
+            || is_empty_size(src_size)
+            || is_empty_target(dst, dst_size)
+        {
+            return;
+        }
+
+        let visible = match clamp_rect(dst_rect, clip).and_then(|r| clamp_to_screen(r, dst_size)) {
+            Some(r) => r,
+            None => return,
+        };
+
         let src_stride = src_size.width as usize;
         let dst_stride = dst_size.width as usize;
 
-        let scale_x = src_rect.width as f32 / dst_rect.width as f32;
-        let scale_y = src_rect.height as f32 / dst_rect.height as f32;
-
-        for dy in 0..dst_rect.height as usize {
-            let dst_y = dst_rect.y as usize + dy;
-            if dst_y >= dst_size.height as usize {
-                continue;
-            }
+        let scale_x = Fixed::from_ratio(src_rect.width as i64, dst_rect.width as i64);
+        let scale_y = Fixed::from_ratio(src_rect.height as i64, dst_rect.height as i64);
 
-            let src_y = src_rect.y as usize + (dy as f32 * scale_y) as usize;
+        for dst_y in visible.y..visible.bottom() {
+            let dy = (dst_y - dst_rect.y) as i64;
+            let src_y = src_rect.y as usize + scale_y.mul_int(dy) as usize;
             if src_y >= src_size.height as usize {
                 continue;
             }
 
-            for dx in 0..dst_rect.width as usize {
-                let dst_x = dst_rect.x as usize + dx;
-                if dst_x >= dst_size.width as usize {
-                    continue;
-                }
-
-                let src_x = src_rect.x as usize + (dx as f32 * scale_x) as usize;
+            for dst_x in visible.x..visible.right() {
+                let dx = (dst_x - dst_rect.x) as i64;
+                let src_x = src_rect.x as usize + scale_x.mul_int(dx) as usize;
                 if src_x >= src_size.width as usize {
                     continue;
                 }
 
                 let src_idx = src_y * src_stride + src_x;
-                let dst_idx = dst_y * dst_stride + dst_x;
+                let dst_idx = dst_y as usize * dst_stride + dst_x as usize;
 
                 if src_idx < src.len() && dst_idx < dst.len() {
-                    let pixel = src[src_idx];
-                    let alpha = pixel >> 24;
+                    let mut pixel = src[src_idx];
+                    let mut alpha = pixel >> 24;
+
+                    if opacity < 255 {
+                        alpha = alpha * opacity as u32 / 255;
+                        pixel = (alpha << 24) | (pixel & 0x00FFFFFF);
+                    }
 
                     if alpha == 0xFF {
                         dst[dst_idx] = pixel;
@@ -193,12 +302,15 @@ impl Blitter {
     /// Preenche retângulo com cor sólida.
     #[inline]
     pub fn fill_rect(dst: &mut [u32], dst_size: Size, rect: Rect, color: Color) {
+        if rect.is_empty() || is_empty_target(dst, dst_size) {
+            return;
+        }
+
         let dst_stride = dst_size.width as usize;
         let color_u32 = color.as_u32();
 
         // Clipping
-        let bounds = Rect::new(0, 0, dst_size.width, dst_size.height);
-        let clipped = match rect.intersection(&bounds) {
+        let clipped = match clamp_to_screen(rect, dst_size) {
             Some(r) => r,
             None => return,
         };
@@ -214,6 +326,70 @@ impl Blitter {
         }
     }
 
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Mesmo resultado de [`Self::fill_rect`], copiando um carimbo de
+    /// `FILL_STAMP_LEN` pixels pré-preenchido em cada linha.
+    ///
+    /// `copy_from_slice` vira `memcpy`, que em alguns alvos é mais rápido que
+    /// `fill` para preenchimentos grandes (ex.: limpar a tela inteira).
+    #[inline]
+    pub fn fill_rect_fast(dst: &mut [u32], dst_size: Size, rect: Rect, color: Color) {
+        if rect.is_empty() || is_empty_target(dst, dst_size) {
+            return;
+        }
+
+        let dst_stride = dst_size.width as usize;
+        let stamp = [color.as_u32(); FILL_STAMP_LEN];
+
+        // Clipping
+        let clipped = match clamp_to_screen(rect, dst_size) {
+            Some(r) => r,
+            None => return,
+        };
+
+        for y in 0..clipped.height as usize {
+            let dst_y = clipped.y as usize + y;
+            let start = dst_y * dst_stride + clipped.x as usize;
+            let end = (start + clipped.width as usize).min(dst.len());
+
+            if start < dst.len() {
+                for chunk in dst[start..end].chunks_mut(FILL_STAMP_LEN) {
+                    chunk.copy_from_slice(&stamp[..chunk.len()]);
+                }
+            }
+        }
+    }
+
+    /// Preenche retângulo com cor translúcida (alpha blending sobre o destino).
+    #[inline]
+    pub fn fill_rect_blend(dst: &mut [u32], dst_size: Size, rect: Rect, color: Color) {
+        if rect.is_empty() || is_empty_target(dst, dst_size) {
+            return;
+        }
+
+        let dst_stride = dst_size.width as usize;
+        let color_u32 = color.as_u32();
+
+        // Clipping
+        let clipped = match clamp_to_screen(rect, dst_size) {
+            Some(r) => r,
+            None => return,
+        };
+
+        for y in 0..clipped.height as usize {
+            let dst_y = clipped.y as usize + y;
+            let start = dst_y * dst_stride + clipped.x as usize;
+            let end = (start + clipped.width as usize).min(dst.len());
+
+            if start < dst.len() {
+                for pixel in &mut dst[start..end] {
+                    *pixel = blend_over(color_u32, *pixel);
+                }
+            }
+        }
+    }
+
     // TODO: Revisar no futuro
     #[allow(unused)]
     /// Preenche retângulo com gradiente horizontal.
@@ -225,9 +401,12 @@ impl Blitter {
         color_left: Color,
         color_right: Color,
     ) {
+        if rect.is_empty() || is_empty_target(dst, dst_size) {
+            return;
+        }
+
         let dst_stride = dst_size.width as usize;
-        let bounds = Rect::new(0, 0, dst_size.width, dst_size.height);
-        let clipped = match rect.intersection(&bounds) {
+        let clipped = match clamp_to_screen(rect, dst_size) {
             Some(r) => r,
             None => return,
         };
@@ -240,9 +419,8 @@ impl Blitter {
                 let idx = dst_y * dst_stride + dst_x;
 
                 if idx < dst.len() {
-                    let t = x as f32 / clipped.width as f32;
-                    let color = color_left.lerp(&color_right, t);
-                    dst[idx] = color.as_u32();
+                    let t = Fixed::from_ratio(x as i64, clipped.width as i64);
+                    dst[idx] = lerp_color(color_left, color_right, t);
                 }
             }
         }
@@ -259,17 +437,20 @@ impl Blitter {
         color_top: Color,
         color_bottom: Color,
     ) {
+        if rect.is_empty() || is_empty_target(dst, dst_size) {
+            return;
+        }
+
         let dst_stride = dst_size.width as usize;
-        let bounds = Rect::new(0, 0, dst_size.width, dst_size.height);
-        let clipped = match rect.intersection(&bounds) {
+        let clipped = match clamp_to_screen(rect, dst_size) {
             Some(r) => r,
             None => return,
         };
 
         for y in 0..clipped.height as usize {
             let dst_y = clipped.y as usize + y;
-            let t = y as f32 / clipped.height as f32;
-            let color = color_top.lerp(&color_bottom, t).as_u32();
+            let t = Fixed::from_ratio(y as i64, clipped.height as i64);
+            let color = lerp_color(color_top, color_bottom, t);
 
             let start = dst_y * dst_stride + clipped.x as usize;
             let end = (start + clipped.width as usize).min(dst.len());
@@ -284,7 +465,7 @@ impl Blitter {
     // EFEITOS
     // =========================================================================
 
-    /// Desenha sombra simples (retângulo com alpha).
+    /// Desenha sombra simples (retângulo com alpha), limitada a `clip`.
     #[inline]
     pub fn draw_shadow(
         dst: &mut [u32],
@@ -293,12 +474,17 @@ impl Blitter {
         offset: Point,
         blur_radius: u32,
         color: Color,
+        clip: Rect,
     ) {
+        if rect.is_empty() || is_empty_target(dst, dst_size) {
+            return;
+        }
+
         let shadow_rect = rect.offset(offset.x, offset.y).expand(blur_radius as i32);
         let dst_stride = dst_size.width as usize;
-        let bounds = Rect::new(0, 0, dst_size.width, dst_size.height);
 
-        let clipped = match shadow_rect.intersection(&bounds) {
+        let clipped = match clamp_to_screen(shadow_rect, dst_size).and_then(|r| clamp_rect(r, clip))
+        {
             Some(r) => r,
             None => return,
         };
@@ -322,40 +508,86 @@ impl Blitter {
     /// Desenha borda de retângulo.
     #[inline]
     pub fn stroke_rect(dst: &mut [u32], dst_size: Size, rect: Rect, thickness: u32, color: Color) {
-        let t = thickness;
-        // Top
-        Self::fill_rect(
+        Self::stroke_rect_clipped(
             dst,
             dst_size,
-            Rect::new(rect.x, rect.y, rect.width, t),
+            rect,
+            thickness,
+            Rect::from_size(dst_size),
             color,
         );
-        // Bottom
-        Self::fill_rect(
-            dst,
-            dst_size,
+    }
+
+    /// Desenha borda de retângulo, limitada a `clip`.
+    #[inline]
+    pub fn stroke_rect_clipped(
+        dst: &mut [u32],
+        dst_size: Size,
+        rect: Rect,
+        thickness: u32,
+        clip: Rect,
+        color: Color,
+    ) {
+        if rect.is_empty() || thickness == 0 {
+            return;
+        }
+
+        let t = thickness;
+        let inner_height = rect.height.saturating_sub(t * 2);
+        let edges = [
+            // Top
+            Rect::new(rect.x, rect.y, rect.width, t),
+            // Bottom
             Rect::new(rect.x, rect.bottom() - t as i32, rect.width, t),
-            color,
-        );
-        // Left
-        Self::fill_rect(
-            dst,
-            dst_size,
-            Rect::new(rect.x, rect.y + t as i32, t, rect.height - t * 2),
-            color,
-        );
-        // Right
-        Self::fill_rect(
-            dst,
-            dst_size,
-            Rect::new(
-                rect.right() - t as i32,
-                rect.y + t as i32,
-                t,
-                rect.height - t * 2,
-            ),
-            color,
-        );
+            // Left
+            Rect::new(rect.x, rect.y + t as i32, t, inner_height),
+            // Right
+            Rect::new(rect.right() - t as i32, rect.y + t as i32, t, inner_height),
+        ];
+
+        for edge in edges {
+            if let Some(r) = clamp_rect(edge, clip) {
+                Self::fill_rect(dst, dst_size, r, color);
+            }
+        }
+    }
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Desenha uma linha de `from` a `to` (Bresenham), recortada ao buffer.
+    pub fn draw_line(dst: &mut [u32], dst_size: Size, from: Point, to: Point, color: Color) {
+        let (mut x, mut y) = (from.x as i64, from.y as i64);
+        let (x1, y1) = (to.x as i64, to.y as i64);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let sx = if x < x1 { 1 } else { -1 };
+        let sy = if y < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            Self::put_pixel(dst, dst_size, x as i32, y as i32, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Liga pontos consecutivos com linhas. Menos de dois pontos não desenha.
+    pub fn draw_polyline(dst: &mut [u32], dst_size: Size, points: &[Point], color: Color) {
+        for pair in points.windows(2) {
+            Self::draw_line(dst, dst_size, pair[0], pair[1], color);
+        }
     }
 
     /// Desenha um pixel com verificação de bounds.
@@ -370,8 +602,6 @@ impl Blitter {
         }
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Desenha um pixel com alpha blending.
     #[inline]
     pub fn put_pixel_blend(dst: &mut [u32], dst_size: Size, x: i32, y: i32, color: Color) {
@@ -385,6 +615,29 @@ impl Blitter {
     }
 }
 
+// =============================================================================
+// AUXILIARES
+// =============================================================================
+
+/// Recorta `rect` ao buffer de destino e a `clip`.
+#[inline]
+fn clip_to_target(rect: Rect, dst_size: Size, clip: Rect) -> Option<Rect> {
+    let bounds = clamp_to_screen(clip, dst_size)?;
+    clamp_rect(rect, bounds)
+}
+
+/// Retorna se um tamanho não tem pixels.
+#[inline]
+fn is_empty_size(size: Size) -> bool {
+    size.width == 0 || size.height == 0
+}
+
+/// Retorna se o destino não tem pixels endereçáveis.
+#[inline]
+fn is_empty_target(dst: &[u32], dst_size: Size) -> bool {
+    dst.is_empty() || is_empty_size(dst_size)
+}
+
 // =============================================================================
 // BLENDING
 // =============================================================================
@@ -454,3 +707,16 @@ fn blend_over_with_dst_alpha(src: u32, dst: u32) -> u32 {
 
     (out_a << 24) | (out_r << 16) | (out_g << 8) | out_b
 }
+
+/// Interpola duas cores ARGB canal a canal (`t` de 0 a `Fixed::ONE`).
+#[inline]
+fn lerp_color(from: Color, to: Color, t: Fixed) -> u32 {
+    let (from, to) = (from.as_u32(), to.as_u32());
+    let channel = |shift: u32| {
+        let a = ((from >> shift) & 0xFF) as i64;
+        let b = ((to >> shift) & 0xFF) as i64;
+        (Fixed::lerp(a, b, t) as u32) << shift
+    };
+
+    channel(24) | channel(16) | channel(8) | channel(0)
+}
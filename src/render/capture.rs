@@ -0,0 +1,97 @@
+//! # Capture
+//!
+//! Cópia dos frames compostos para um anel de buffers SHM (gravação de tela).
+//!
+//! Cada frame apresentado vai para o próximo buffer do anel. Se o gravador
+//! ainda não liberou esse buffer, o frame é descartado (e contado) em vez de
+//! bloquear o compositor.
+
+use alloc::vec::Vec;
+use redpowder::ipc::SharedMemory;
+
+use crate::shm::as_u32_slice_mut;
+
+// =============================================================================
+// FRAME CAPTURADO
+// =============================================================================
+
+/// Frame copiado para o anel, a ser anunciado ao gravador.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Índice do buffer preenchido.
+    pub index: u32,
+    /// Total de frames descartados até agora.
+    pub dropped: u32,
+}
+
+// =============================================================================
+// CAPTURE SINK
+// =============================================================================
+
+/// Anel de buffers de captura.
+pub struct CaptureSink {
+    /// Buffers do anel.
+    buffers: Vec<SharedMemory>,
+    /// Buffer preenchido e ainda não liberado pelo gravador.
+    busy: Vec<bool>,
+    /// Próximo buffer a preencher (ordem fixa do anel).
+    next: usize,
+    /// Frames descartados (gravador atrasado).
+    dropped: u32,
+    /// Frames preenchidos desde o último `take_filled`.
+    filled: Vec<CapturedFrame>,
+}
+
+impl CaptureSink {
+    /// Cria o anel com os buffers dados.
+    pub fn new(buffers: Vec<SharedMemory>) -> Self {
+        let count = buffers.len();
+        Self {
+            buffers,
+            busy: alloc::vec![false; count],
+            next: 0,
+            dropped: 0,
+            filled: Vec::new(),
+        }
+    }
+
+    /// Copia um frame para o próximo buffer, ou descarta se estiver ocupado.
+    pub fn capture(&mut self, pixels: &[u32]) {
+        if self.buffers.is_empty() {
+            return;
+        }
+
+        let index = self.next;
+        if self.busy[index] {
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+
+        let shm = &mut self.buffers[index];
+        let count = pixels.len().min(shm.size() / 4);
+        let dst = match as_u32_slice_mut(shm, count) {
+            Some(dst) => dst,
+            None => return,
+        };
+        dst.copy_from_slice(&pixels[..count]);
+
+        self.busy[index] = true;
+        self.next = (index + 1) % self.buffers.len();
+        self.filled.push(CapturedFrame {
+            index: index as u32,
+            dropped: self.dropped,
+        });
+    }
+
+    /// Gravador terminou de ler o buffer `index`.
+    pub fn release(&mut self, index: u32) {
+        if let Some(busy) = self.busy.get_mut(index as usize) {
+            *busy = false;
+        }
+    }
+
+    /// Retorna e limpa os frames preenchidos.
+    pub fn take_filled(&mut self) -> Vec<CapturedFrame> {
+        core::mem::take(&mut self.filled)
+    }
+}
@@ -9,16 +9,33 @@
 //! - Desenhar cursor e efeitos
 //! - Apresentar frames no display
 
+use super::backbuffer::Backbuffer;
 use super::blitter::Blitter;
-use crate::scene::{DamageTracker, LayerManager, Window, WindowId};
-use alloc::collections::BTreeMap;
+use super::capture::{CaptureSink, CapturedFrame};
+use super::easing::Easing;
+#[cfg(feature = "selftest")]
+use super::selftest::{self, SelfTestReport};
+use super::shm_pool::ShmPool;
+use super::wallpaper::{Wallpaper, WallpaperMode};
+use crate::scene::placement::work_area;
+use crate::scene::{
+    area_checked, clamp_rect, clamp_to_screen, rects_intersect, subtract_rect, DamageTracker,
+    FullscreenRestore, LayerManager, Window, WindowId, WindowIdAllocator,
+};
+#[cfg(feature = "cursor-trail")]
+use crate::ui::cursor_trail::CursorTrail;
+use crate::ui::decoration::{draw_focus_ring, FOCUS_RING_COLOR, TITLEBAR_HEIGHT};
+use crate::ui::notification::{NotificationManager, Toast};
+use crate::ui::preedit::Preedit;
+use crate::ui::splash::{self, SplashState};
+use crate::ui::window_menu::WindowMenu;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::String;
-use alloc::vec;
 use alloc::vec::Vec;
 use gfx_types::color::Color;
 use gfx_types::display::DisplayInfo;
 use gfx_types::geometry::{Point, Rect, Size};
-use gfx_types::window::LayerType;
+use gfx_types::window::{LayerType, WindowState};
 use redpowder::graphics::write_pixels;
 use redpowder::ipc::SharedMemory;
 use redpowder::syscall::SysResult;
@@ -39,6 +56,101 @@ const SHADOW_OFFSET: Point = Point { x: 4, y: 4 };
 /// Blur radius da sombra.
 const SHADOW_BLUR: u32 = 8;
 
+/// Duração do fade-in de janelas novas (ms).
+const FADE_IN_MS: u64 = 150;
+
+/// Parte mínima (px) de uma janela que permanece na tela após mudança de modo.
+const MIN_VISIBLE_AFTER_RESIZE: i32 = 32;
+
+/// Escurecimento aplicado a janelas sem foco (o alpha define a intensidade).
+const INACTIVE_DIM_COLOR: Color = Color(0x50000000);
+
+/// Commits por janela em um frame acima dos quais o cliente está em flood.
+const COMMIT_THROTTLE_LIMIT: u32 = 4;
+
+/// Orçamento de trabalho por frame, em telas inteiras de pixels escritos
+/// (fundo e wallpaper inclusive).
+///
+/// Não há relógio de alta resolução no compositor (o tempo é derivado do
+/// contador de frames), então o custo do frame é medido pelos pixels
+/// escritos. Acima do orçamento, efeitos não essenciais ficam para depois.
+const FRAME_WORK_BUDGET_SCREENS: u64 = 4;
+
+// =============================================================================
+// SAFE MODE
+// =============================================================================
+
+/// Falhas seguidas de alocação de SHM até entrar em modo seguro.
+const SAFE_MODE_FAILURE_THRESHOLD: u32 = 3;
+
+/// Modo seguro: com pouca memória, efeitos que alocam ou custam caro
+/// (captura, wallpaper, fade-in, sombras) são desligados e só a composição
+/// básica continua.
+#[derive(Clone, Copy, Debug)]
+pub struct SafeMode {
+    /// Falhas de alocação seguidas.
+    failures: u32,
+    /// Falhas seguidas que ativam o modo.
+    threshold: u32,
+    /// Modo seguro ativo (só sai por configuração explícita).
+    active: bool,
+}
+
+impl SafeMode {
+    /// Cria o estado com o limite padrão.
+    pub fn new() -> Self {
+        Self {
+            failures: 0,
+            threshold: SAFE_MODE_FAILURE_THRESHOLD,
+            active: false,
+        }
+    }
+
+    /// Registra o resultado de uma alocação. Retorna true se o modo seguro
+    /// acabou de ser ativado.
+    pub fn record(&mut self, allocated: bool) -> bool {
+        if allocated {
+            self.failures = 0;
+            return false;
+        }
+
+        self.failures = self.failures.saturating_add(1);
+        if !self.active && self.failures >= self.threshold {
+            self.active = true;
+            return true;
+        }
+        false
+    }
+
+    /// Modo seguro ativo?
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+impl Default for SafeMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// FRAME STATS
+// =============================================================================
+
+/// Estatísticas de escrita do último frame composto.
+///
+/// `pixels_written / damaged_pixels` é o overdraw: quantas vezes, em média,
+/// cada pixel danificado foi escrito (fundo, wallpaper, sombras, janelas).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Pixels escritos no backbuffer.
+    pub pixels_written: u64,
+    /// Pixels da região danificada (clip do frame).
+    pub damaged_pixels: u64,
+}
+
 // =============================================================================
 // RENDER ENGINE
 // =============================================================================
@@ -48,15 +160,29 @@ pub struct RenderEngine {
     /// Informações do display.
     display_info: DisplayInfo,
     /// Backbuffer em RAM.
-    backbuffer: Vec<u32>,
+    backbuffer: Backbuffer,
     /// Gerenciador de camadas.
     layers: LayerManager,
     /// Janelas registradas.
     windows: BTreeMap<u32, Window>,
     /// Tracker de damage.
     damage: DamageTracker,
-    /// Próximo ID de janela.
-    next_window_id: u32,
+    /// Janelas modificadas desde o último frame.
+    dirty_windows: BTreeSet<u32>,
+    /// Commits recebidos por janela neste frame.
+    commit_counts: BTreeMap<u32, u32>,
+    /// Janelas aguardando frame callback.
+    frame_requests: BTreeSet<u32>,
+    /// Janelas com frame callback pronto (compostas no último frame).
+    frames_done: Vec<u32>,
+    /// Frame callbacks de janelas em flood, adiados para o próximo frame.
+    deferred_frames: Vec<u32>,
+    /// Janelas cuja SHM deixou de estar mapeada (a destruir pelo servidor).
+    lost_windows: Vec<u32>,
+    /// Alocador de IDs de janela.
+    window_ids: WindowIdAllocator,
+    /// Próximo `z_order` (cresce a cada janela criada ou trazida à frente).
+    next_z_order: u32,
     /// Contador de frames.
     frame_count: u64,
     /// Janela com foco.
@@ -65,13 +191,62 @@ pub struct RenderEngine {
     cursor_pos: Point,
     /// Cursor visível.
     cursor_visible: bool,
+    /// Escurecer janelas normais sem foco.
+    dim_inactive: bool,
+    /// Overlay preto de inatividade (0 = desligado, 255 = tela apagada).
+    idle_dim: u8,
+    /// Tela apagada já foi apresentada (não é preciso recompor).
+    blank_presented: bool,
+    /// Tela de abertura (até a primeira janela).
+    splash: SplashState,
+    /// Fade-in ao exibir janelas novas.
+    fade_in: bool,
+    /// Curva do fade-in de janelas novas.
+    fade_easing: Easing,
+    /// Janelas em fade-in (ID → início em ms).
+    fading_windows: BTreeMap<u32, u64>,
+    /// Pular a apresentação quando o conteúdo danificado não mudou.
+    skip_identical_frames: bool,
+    /// O último present falhou: o backbuffer pode estar à frente da tela,
+    /// então o próximo frame apresenta mesmo sem mudança.
+    present_incomplete: bool,
+    /// Notificações (toasts) ativas.
+    notifications: NotificationManager,
+    /// Imagem de fundo (None = apenas cor de fundo).
+    wallpaper: Option<Wallpaper>,
+    /// Modo de posicionamento do wallpaper.
+    wallpaper_mode: WallpaperMode,
+    /// Tempo atual (ms), usado pelas animações.
+    now_ms: u64,
+    /// Menu de janela aberto (botão direito na barra de título).
+    window_menu: Option<WindowMenu>,
+    /// Texto de composição do IME (preedit) em exibição.
+    preedit: Option<Preedit>,
+    /// Anel de captura para gravação de tela (None = desligada).
+    capture: Option<CaptureSink>,
+    /// Modo seguro (efeitos desligados por falta de memória).
+    safe_mode: SafeMode,
+    /// Buffers SHM de janelas destruídas, para reaproveitar.
+    shm_pool: ShmPool,
+    /// Pixels escritos no frame atual (ver `FRAME_WORK_BUDGET_SCREENS`).
+    frame_work: u64,
+    /// Estatísticas do último frame composto.
+    frame_stats: FrameStats,
+    /// Janelas cuja sombra foi adiada por estourar o orçamento do frame.
+    deferred_effects: BTreeSet<u32>,
+    /// Rastro do cursor (diagnóstico).
+    #[cfg(feature = "cursor-trail")]
+    cursor_trail: CursorTrail,
 }
 
 impl RenderEngine {
     /// Cria novo motor de renderização.
     pub fn new(display_info: DisplayInfo) -> Self {
-        let size = (display_info.width * display_info.height) as usize;
-        let backbuffer = vec![BACKGROUND_COLOR.as_u32(); size];
+        let size = area_checked(Size::new(display_info.width, display_info.height)).unwrap_or(0);
+        let backbuffer = Backbuffer::new(
+            Size::new(display_info.width, display_info.height),
+            BACKGROUND_COLOR,
+        );
 
         redpowder::println!(
             "[Render] Backbuffer criado: {}x{} ({} KB)",
@@ -89,11 +264,41 @@ impl RenderEngine {
             layers: LayerManager::new(),
             windows: BTreeMap::new(),
             damage,
-            next_window_id: 1,
+            dirty_windows: BTreeSet::new(),
+            commit_counts: BTreeMap::new(),
+            frame_requests: BTreeSet::new(),
+            frames_done: Vec::new(),
+            deferred_frames: Vec::new(),
+            lost_windows: Vec::new(),
+            window_ids: WindowIdAllocator::new(),
+            next_z_order: 1,
             frame_count: 0,
             focused_window: None,
             cursor_pos: Point::ZERO,
             cursor_visible: true,
+            dim_inactive: false,
+            idle_dim: 0,
+            blank_presented: false,
+            splash: SplashState::Showing,
+            fade_in: true,
+            fade_easing: Easing::EaseOutCubic,
+            fading_windows: BTreeMap::new(),
+            skip_identical_frames: true,
+            present_incomplete: false,
+            notifications: NotificationManager::new(),
+            wallpaper: None,
+            wallpaper_mode: WallpaperMode::default(),
+            now_ms: 0,
+            window_menu: None,
+            preedit: None,
+            capture: None,
+            safe_mode: SafeMode::new(),
+            shm_pool: ShmPool::new(),
+            frame_work: 0,
+            frame_stats: FrameStats::default(),
+            deferred_effects: BTreeSet::new(),
+            #[cfg(feature = "cursor-trail")]
+            cursor_trail: CursorTrail::new(),
         }
     }
 
@@ -107,8 +312,6 @@ impl RenderEngine {
         Size::new(self.display_info.width, self.display_info.height)
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Retorna informações do display.
     #[inline]
     pub fn display_info(&self) -> &DisplayInfo {
@@ -123,29 +326,122 @@ impl RenderEngine {
         self.frame_count
     }
 
+    /// Retorna a cor composta em (x, y) no último frame apresentado.
+    ///
+    /// Inclui overlays e o cursor, como aparecem na tela.
+    pub fn backbuffer_pixel(&self, x: i32, y: i32) -> Option<u32> {
+        self.backbuffer.pixel(x, y)
+    }
+
     /// Retorna estatísticas.
     pub fn stats(&self) -> (u64, usize) {
         (self.frame_count, self.windows.len())
     }
 
+    /// Executa o self-test de diagnóstico: barras de cor, gradiente e um
+    /// bloco em movimento, desenhados direto no backbuffer e apresentados.
+    ///
+    /// Bloqueia por `BLOCK_FRAMES` frames (cerca de meio segundo); depois a
+    /// tela inteira é recomposta com as janelas.
+    #[cfg(feature = "selftest")]
+    pub fn run_self_test(&mut self) -> SelfTestReport {
+        let size = self.size();
+        let mut report = SelfTestReport::default();
+
+        selftest::draw_color_bars(self.backbuffer.pixels_mut(), size);
+        report.fill = selftest::check_color_bars(self.backbuffer.pixels(), size);
+        report.blit = selftest::blit_gradient(self.backbuffer.pixels_mut(), size);
+
+        report.present = self.present().is_ok();
+        for frame in 0..selftest::BLOCK_FRAMES {
+            selftest::draw_moving_block(self.backbuffer.pixels_mut(), size, frame);
+            report.present &= self.present().is_ok();
+            let _ = redpowder::time::sleep(selftest::BLOCK_FRAME_MS);
+        }
+
+        redpowder::println!("[Render] Self-test: {:?}", report);
+        self.full_screen_damage();
+        report
+    }
+
+    /// Pixels escritos e danificados no último frame composto.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+    }
+
+    // =========================================================================
+    // DISPLAY
+    // =========================================================================
+
+    /// Aplica uma nova resolução de display.
+    ///
+    /// Realoca o backbuffer, ajusta janelas maximizadas ao novo tamanho e
+    /// traz de volta janelas que ficaram fora da tela. Retorna os IDs das
+    /// janelas cuja geometria mudou.
+    pub fn reconfigure_display(&mut self, display_info: DisplayInfo) -> Vec<u32> {
+        let size = Size::new(display_info.width, display_info.height);
+        redpowder::println!(
+            "[Render] Display reconfigurado: {}x{} -> {}x{}",
+            self.display_info.width,
+            self.display_info.height,
+            size.width,
+            size.height
+        );
+
+        self.display_info = display_info;
+        self.backbuffer = Backbuffer::new(size, BACKGROUND_COLOR);
+        self.damage.set_size(size.width, size.height);
+
+        let max_x = size.width as i32 - MIN_VISIBLE_AFTER_RESIZE;
+        let max_y = size.height as i32 - MIN_VISIBLE_AFTER_RESIZE;
+
+        let area = self.work_area();
+        let mut changed = Vec::new();
+        for (id, window) in self.windows.iter_mut() {
+            let before = window.rect();
+
+            if window.is_fullscreen() {
+                window.position = Point::ZERO;
+                window.size = size;
+            } else if window.state == WindowState::Maximized {
+                window.position = Point::new(area.x, area.y);
+                window.size = Size::new(area.width, area.height);
+            } else {
+                let min_x = MIN_VISIBLE_AFTER_RESIZE - window.size.width as i32;
+                let x = window.position.x.min(max_x).max(min_x);
+                let y = window.position.y.min(max_y).max(0);
+                window.position = Point::new(x, y);
+            }
+
+            if window.rect() != before {
+                window.dirty = true;
+                self.dirty_windows.insert(*id);
+                changed.push(*id);
+            }
+        }
+
+        self.full_screen_damage();
+        changed
+    }
+
     // =========================================================================
     // JANELAS
     // =========================================================================
 
-    /// Cria nova janela.
+    /// Cria nova janela. Retorna None se não houver IDs livres.
     pub fn create_window(
         &mut self,
         size: Size,
         shm: SharedMemory,
         layer: LayerType,
         title: String,
-    ) -> u32 {
-        let id = self.next_window_id;
-        self.next_window_id += 1;
+    ) -> Option<u32> {
+        let id = self.window_ids.allocate()?.0;
 
         let mut window = Window::new(id, size, shm);
         window.layer = layer;
         window.title = title.clone();
+        window.z_order = self.take_z_order();
 
         redpowder::println!(
             "[Render] Janela {} criada ({}x{}) layer={:?} '{}'",
@@ -159,11 +455,15 @@ impl RenderEngine {
         self.windows.insert(id, window);
         self.layers.add_window_to_layer(WindowId(id), layer);
         self.damage.add(Rect::from_size(size));
+        self.splash.dismiss(self.now_ms);
 
-        id
+        Some(id)
     }
 
     /// Obtém janela por ID.
+    ///
+    /// IDs de janelas destruídas nunca voltam a ser válidos, mesmo após o
+    /// slot ser reaproveitado (a geração difere).
     #[inline]
     pub fn get_window(&self, id: u32) -> Option<&Window> {
         self.windows.get(&id)
@@ -175,15 +475,36 @@ impl RenderEngine {
         self.windows.get_mut(&id)
     }
 
-    /// Destrói janela.
-    pub fn destroy_window(&mut self, id: u32) {
+    /// Destrói janela. O buffer volta ao pool para outra janela de `owner`
+    /// (o cliente dono, que ainda o mapeia).
+    pub fn destroy_window(&mut self, id: u32, owner: &str) {
         if let Some(window) = self.windows.remove(&id) {
-            self.damage.add(window.rect());
+            self.damage.add(paint_bounds(&window));
+            if !self.safe_mode.is_active() && window.is_mapped() {
+                self.shm_pool.recycle(window.shm, owner, self.now_ms);
+            }
             self.layers.remove_window(WindowId(id));
+            self.window_ids.release(WindowId(id));
+            self.dirty_windows.remove(&id);
+            self.fading_windows.remove(&id);
+            self.frame_requests.remove(&id);
+            self.commit_counts.remove(&id);
+            self.deferred_frames.retain(|&w| w != id);
+            for other in self.windows.values_mut() {
+                if other.transient_for == Some(WindowId(id)) {
+                    other.transient_for = None;
+                }
+            }
 
             if self.focused_window == Some(id) {
                 self.focused_window = None;
             }
+            if self.window_menu.is_some_and(|menu| menu.window_id == id) {
+                self.close_window_menu();
+            }
+            if self.preedit.as_ref().is_some_and(|p| p.window_id == id) {
+                self.set_preedit(id, String::new(), Point::ZERO);
+            }
 
             redpowder::println!("[Render] Janela {} destruída", id);
         }
@@ -192,43 +513,218 @@ impl RenderEngine {
     /// Move janela para nova posição.
     pub fn move_window(&mut self, id: u32, x: i32, y: i32) {
         if let Some(window) = self.windows.get_mut(&id) {
-            self.damage.add(window.rect());
+            self.damage.add(paint_bounds(window));
             window.move_to(x, y);
-            self.damage.add(window.rect());
+            self.damage.add(paint_bounds(window));
+            self.dirty_windows.insert(id);
         }
     }
 
-    /// Traz janela para a frente.
+    /// Traz janela para a frente, com seus diálogos (transients) logo acima.
     pub fn bring_to_front(&mut self, id: u32) {
-        if let Some(window) = self.windows.get(&id) {
+        let z_order = self.take_z_order();
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.z_order = z_order;
             let layer = window.layer;
-            self.layers.get_mut(layer).bring_to_front(WindowId(id));
+            self.layers
+                .layer_of_mut(WindowId(id), layer)
+                .bring_to_front(WindowId(id));
             self.damage.add(window.rect());
         }
+
+        // Na ordem atual entre eles; ciclos são recusados em set_transient_for
+        for child in self.transients_of(id) {
+            self.bring_to_front(child);
+        }
+    }
+
+    /// Diálogos de `parent`, de baixo para cima.
+    fn transients_of(&self, parent: u32) -> Vec<u32> {
+        self.layers
+            .iter_bottom_to_top()
+            .filter(|id| {
+                self.windows
+                    .get(&id.0)
+                    .is_some_and(|w| w.transient_for == Some(WindowId(parent)))
+            })
+            .map(|id| id.0)
+            .collect()
+    }
+
+    /// Define a janela mãe de um diálogo e o traz para cima dela. Recusa
+    /// (retorna false) janelas inexistentes e ciclos.
+    pub fn set_transient_for(&mut self, id: u32, parent: Option<u32>) -> bool {
+        if !self.windows.contains_key(&id) {
+            return false;
+        }
+        if let Some(parent) = parent {
+            // A mãe não pode descender do próprio diálogo
+            let mut ancestor = Some(parent);
+            while let Some(current) = ancestor {
+                if current == id {
+                    return false;
+                }
+                ancestor = match self.windows.get(&current) {
+                    Some(w) => w.transient_for.map(|p| p.0),
+                    None => return false,
+                };
+            }
+        }
+
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.transient_for = parent.map(WindowId);
+        }
+        if parent.is_some() {
+            self.bring_to_front(id);
+        }
+        true
+    }
+
+    /// Reserva o próximo `z_order`, acima de todas as janelas existentes.
+    ///
+    /// A ordem de pintura vem do [`LayerManager`]; o `z_order` acompanha essa
+    /// ordem para que janelas da mesma camada sejam comparáveis.
+    fn take_z_order(&mut self) -> u32 {
+        let z_order = self.next_z_order;
+        self.next_z_order = self.next_z_order.saturating_add(1);
+        z_order
     }
 
     // TODO: Revisar no futuro
     #[allow(unused)]
-    /// Envia janela para trás.
+    /// Envia janela para trás (diálogos não descem abaixo da mãe).
     pub fn send_to_back(&mut self, id: u32) {
-        if let Some(window) = self.windows.get(&id) {
+        let layer = match self.windows.get(&id) {
+            Some(w) if w.transient_for.is_none() => w.layer,
+            _ => return,
+        };
+        let bottom = self
+            .windows
+            .values()
+            .filter(|w| w.layer == layer)
+            .map(|w| w.z_order)
+            .min()
+            .unwrap_or(0);
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.z_order = bottom.saturating_sub(1);
             let layer = window.layer;
-            self.layers.get_mut(layer).send_to_back(WindowId(id));
+            self.layers
+                .layer_of_mut(WindowId(id), layer)
+                .send_to_back(WindowId(id));
             self.damage.add(window.rect());
         }
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
-    /// Altera layer de uma janela.
+    /// Altera layer de uma janela (a janela vai para o topo da nova camada).
     pub fn set_window_layer(&mut self, id: u32, new_layer: LayerType) {
+        let z_order = self.take_z_order();
         if let Some(window) = self.windows.get_mut(&id) {
             let old_layer = window.layer;
             if old_layer != new_layer {
                 self.layers.move_window(WindowId(id), old_layer, new_layer);
+                window.z_order = z_order;
                 window.set_layer(new_layer);
-                self.damage.add(window.rect());
+                self.damage.add(paint_bounds(window));
+                self.dirty_windows.insert(id);
+            } else if new_layer == LayerType::Background {
+                // Widget de desktop voltando para o fundo comum
+                self.layers.set_desktop_widget(WindowId(id), false);
+                self.damage.add(paint_bounds(window));
+            }
+        }
+    }
+
+    /// Torna a janela um widget de desktop: camada `Background`, acima do
+    /// wallpaper e abaixo das janelas normais.
+    pub fn set_desktop_widget(&mut self, id: u32) {
+        if !self.windows.contains_key(&id) {
+            return;
+        }
+        self.set_window_layer(id, LayerType::Background);
+        self.layers.set_desktop_widget(WindowId(id), true);
+        self.mark_damage(id);
+    }
+
+    /// Define o fator de escala da janela (1 = sem escala).
+    pub fn set_window_scale(&mut self, id: u32, scale: u8) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            let before = paint_bounds(window);
+            window.set_scale(scale);
+            self.damage.add(before);
+            self.damage.add(paint_bounds(window));
+            self.dirty_windows.insert(id);
+        }
+    }
+
+    /// Enrola a janela (só a barra de título é composta) ou desenrola.
+    /// Retorna true se o estado mudou.
+    pub fn set_shaded(&mut self, id: u32, shaded: bool) -> bool {
+        let window = match self.windows.get_mut(&id) {
+            Some(w) if w.is_shaded() != shaded && !w.is_fullscreen() => w,
+            _ => return false,
+        };
+
+        let before = paint_bounds(window);
+        window.set_shaded(shaded, TITLEBAR_HEIGHT);
+        self.damage.add(before);
+        self.damage.add(paint_bounds(window));
+        self.dirty_windows.insert(id);
+        true
+    }
+
+    /// Entra ou sai da tela cheia.
+    ///
+    /// Em tela cheia a janela cobre o display inteiro, sem decorações, na
+    /// camada Overlay (acima dos painéis). Ao sair, volta ao retângulo e à
+    /// camada anteriores. Retorna true se o estado mudou.
+    pub fn set_fullscreen(&mut self, id: u32, enabled: bool) -> bool {
+        let size = self.size();
+        let z_order = self.next_z_order;
+        let window = match self.windows.get_mut(&id) {
+            Some(w) => w,
+            None => return false,
+        };
+        if window.is_fullscreen() == enabled {
+            return false;
+        }
+
+        let old_layer = window.layer;
+        let new_layer = match window.fullscreen_restore.take() {
+            None => {
+                window.set_shaded(false, TITLEBAR_HEIGHT);
+                window.fullscreen_restore = Some(FullscreenRestore {
+                    rect: window.rect(),
+                    layer: old_layer,
+                });
+                window.position = Point::ZERO;
+                window.size = size;
+                LayerType::Overlay
+            }
+            Some(restore) => {
+                window.position = Point::new(restore.rect.x, restore.rect.y);
+                window.size = Size::new(restore.rect.width, restore.rect.height);
+                restore.layer
             }
+        };
+
+        if old_layer != new_layer {
+            self.layers.move_window(WindowId(id), old_layer, new_layer);
+            window.set_layer(new_layer);
+            window.z_order = z_order;
+            self.next_z_order = z_order.saturating_add(1);
+        }
+        window.dirty = true;
+        self.dirty_windows.insert(id);
+        self.full_screen_damage();
+        true
+    }
+
+    /// Altera a opacidade global de uma janela.
+    pub fn set_window_opacity(&mut self, id: u32, opacity: u8) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.set_opacity(opacity);
+            self.damage.add(paint_bounds(window));
+            self.dirty_windows.insert(id);
         }
     }
 
@@ -238,14 +734,71 @@ impl RenderEngine {
             if !window.has_content {
                 window.set_has_content();
                 self.damage.add(window.rect());
+                if self.fade_in && !self.safe_mode.is_active() {
+                    self.fading_windows.insert(id, self.now_ms);
+                }
+            }
+        }
+    }
+
+    /// Registra um commit de buffer da janela.
+    ///
+    /// Vários commits no mesmo frame são coalescidos: apenas o primeiro
+    /// gera damage, já que todos cobrem a mesma região. Acima de
+    /// `COMMIT_THROTTLE_LIMIT` o frame callback da janela atrasa um frame.
+    pub fn commit_window(&mut self, id: u32) {
+        if !self.windows.contains_key(&id) {
+            return;
+        }
+
+        let count = self.commit_counts.entry(id).or_insert(0);
+        *count = count.saturating_add(1);
+        match *count {
+            1 => {
+                self.mark_window_has_content(id);
+                self.mark_damage(id);
+            }
+            n if n == COMMIT_THROTTLE_LIMIT + 1 => {
+                redpowder::println!("[Render] Janela {} em flood de commits", id);
             }
+            _ => {}
+        }
+    }
+
+    /// Pede um frame callback: a janela entra em [`Self::take_frames_done`]
+    /// após o próximo frame em que for composta.
+    pub fn request_frame(&mut self, id: u32) {
+        if self.windows.contains_key(&id) {
+            self.frame_requests.insert(id);
+        }
+    }
+
+    /// Retorna (e limpa) as janelas cujo frame callback deve ser enviado.
+    pub fn take_frames_done(&mut self) -> Vec<u32> {
+        core::mem::take(&mut self.frames_done)
+    }
+
+    /// Retorna (e limpa) as janelas com SHM revogada, que devem ser destruídas.
+    pub fn take_lost_windows(&mut self) -> Vec<u32> {
+        core::mem::take(&mut self.lost_windows)
+    }
+
+    /// Força o redesenho completo da janela (incluindo sombra).
+    ///
+    /// Diferente de [`Self::commit_window`], nunca é coalescido.
+    pub fn refresh_window(&mut self, id: u32) -> bool {
+        if !self.windows.contains_key(&id) {
+            return false;
         }
+        self.mark_damage(id);
+        true
     }
 
     /// Marca janela como danificada.
     pub fn mark_damage(&mut self, id: u32) {
         if let Some(window) = self.windows.get(&id) {
-            self.damage.add(window.rect());
+            self.damage.add(paint_bounds(window));
+            self.dirty_windows.insert(id);
         }
     }
 
@@ -255,26 +808,242 @@ impl RenderEngine {
             .damage_full(self.display_info.width, self.display_info.height);
     }
 
+    /// Força a recomposição de uma área da tela, independente de janelas
+    /// (ex.: após escrita externa no framebuffer). Retorna a área aceita,
+    /// recortada à tela.
+    pub fn damage_region(&mut self, rect: Rect) -> Option<Rect> {
+        let clamped = clamp_to_screen(rect, self.size())?;
+        self.damage.add(clamped);
+        Some(clamped)
+    }
+
     // =========================================================================
-    // HIT TESTING
+    // MENU DE JANELA
     // =========================================================================
 
-    /// Retorna ID da janela na posição dada (se houver).
-    pub fn window_at_point(&self, x: i32, y: i32) -> Option<u32> {
-        for window_id in self.layers.iter_top_to_bottom() {
-            if let Some(window) = self.windows.get(&window_id.0) {
-                if window.is_visible() && window.contains_point(x, y) {
-                    return Some(window_id.0);
-                }
+    /// Abre o menu de janela (fecha o anterior).
+    pub fn open_window_menu(&mut self, menu: WindowMenu) {
+        self.close_window_menu();
+        self.damage.add(menu.rect());
+        self.window_menu = Some(menu);
+    }
+
+    /// Fecha o menu de janela, retornando-o.
+    pub fn close_window_menu(&mut self) -> Option<WindowMenu> {
+        let menu = self.window_menu.take()?;
+        self.damage.add(menu.rect());
+        Some(menu)
+    }
+
+    /// Menu de janela aberto.
+    pub fn window_menu(&self) -> Option<&WindowMenu> {
+        self.window_menu.as_ref()
+    }
+
+    /// Move a seleção do menu de janela (próximo ou anterior).
+    pub fn select_window_menu_item(&mut self, next: bool) {
+        if let Some(menu) = &mut self.window_menu {
+            if next {
+                menu.select_next();
+            } else {
+                menu.select_prev();
+            }
+            self.damage.add(menu.rect());
+        }
+    }
+
+    // =========================================================================
+    // PREEDIT (IME)
+    // =========================================================================
+
+    /// Exibe o texto de composição em `local` (coordenadas da janela).
+    /// Texto vazio remove a composição da janela.
+    pub fn set_preedit(&mut self, window_id: u32, text: String, local: Point) {
+        if let Some(old) = self.preedit.take() {
+            if old.window_id != window_id && text.is_empty() {
+                self.preedit = Some(old);
+                return;
             }
+            self.damage.add(old.rect());
+        }
+        if text.is_empty() {
+            return;
+        }
+
+        let origin = match self.windows.get(&window_id) {
+            Some(w) => w.position,
+            None => return,
+        };
+        let position = Point::new(
+            origin.x.saturating_add(local.x),
+            origin.y.saturating_add(local.y),
+        );
+        let preedit = Preedit::new(window_id, text, position);
+        self.damage.add(preedit.rect());
+        self.preedit = Some(preedit);
+    }
+
+    // =========================================================================
+    // BUFFERS SHM
+    // =========================================================================
+
+    /// Aloca o buffer de uma janela nova, reaproveitando um buffer livre da
+    /// mesma classe de tamanho que já era de `owner` quando houver. Pode ser
+    /// maior que `bytes`.
+    pub fn allocate_window_buffer(&mut self, bytes: usize, owner: &str) -> SysResult<SharedMemory> {
+        let shm = self.shm_pool.allocate(bytes, owner);
+        self.record_allocation(shm.is_ok());
+        shm
+    }
+
+    // =========================================================================
+    // MODO SEGURO
+    // =========================================================================
+
+    /// Registra o resultado de uma alocação de SHM, entrando em modo seguro
+    /// após falhas seguidas demais.
+    pub fn record_allocation(&mut self, allocated: bool) {
+        if self.safe_mode.record(allocated) {
+            self.enter_safe_mode();
+        }
+    }
+
+    /// Liga/desliga o modo seguro manualmente.
+    pub fn set_safe_mode(&mut self, enabled: bool) {
+        if enabled && !self.safe_mode.active {
+            self.safe_mode.active = true;
+            self.enter_safe_mode();
+        } else if !enabled && self.safe_mode.active {
+            self.safe_mode = SafeMode::new();
+            self.full_screen_damage();
+            redpowder::println!("[Render] Modo seguro desligado");
+        }
+    }
+
+    /// Define quantas falhas seguidas de alocação ativam o modo seguro.
+    pub fn set_safe_mode_threshold(&mut self, threshold: u32) {
+        self.safe_mode.threshold = threshold.max(1);
+    }
+
+    /// Modo seguro ativo?
+    #[inline]
+    pub fn safe_mode_active(&self) -> bool {
+        self.safe_mode.is_active()
+    }
+
+    /// Libera os efeitos que ocupam memória e recompõe a tela sem eles.
+    fn enter_safe_mode(&mut self) {
+        self.shm_pool.clear();
+        self.capture = None;
+        self.wallpaper = None;
+        self.fading_windows.clear();
+        self.full_screen_damage();
+        redpowder::println!("[Render] Pouca memória: modo seguro ativado (efeitos desligados)");
+    }
+
+    // =========================================================================
+    // CAPTURA
+    // =========================================================================
+
+    /// Liga a captura: cada frame apresentado é copiado para o anel
+    /// `shm_buffers`. Lista vazia desliga.
+    pub fn set_capture_sink(&mut self, shm_buffers: Vec<SharedMemory>) {
+        self.capture = if shm_buffers.is_empty() || self.safe_mode.is_active() {
+            None
+        } else {
+            // Primeiro frame capturado deve ser completo
+            self.full_screen_damage();
+            Some(CaptureSink::new(shm_buffers))
+        };
+    }
+
+    /// Gravador liberou o buffer `index` do anel.
+    pub fn release_capture_buffer(&mut self, index: u32) {
+        if let Some(capture) = &mut self.capture {
+            capture.release(index);
         }
-        None
+    }
+
+    /// Retorna e limpa os frames capturados desde a última chamada.
+    pub fn take_captured_frames(&mut self) -> Vec<CapturedFrame> {
+        self.capture
+            .as_mut()
+            .map(|capture| capture.take_filled())
+            .unwrap_or_default()
+    }
+
+    // =========================================================================
+    // ÁREA DE TRABALHO
+    // =========================================================================
+
+    /// Tela sem as faixas reservadas pelos painéis visíveis (camada Panel).
+    pub fn work_area(&self) -> Rect {
+        let panels = self
+            .windows
+            .values()
+            .filter(|w| w.layer == LayerType::Panel && w.state != WindowState::Minimized)
+            .map(|w| w.rect());
+        work_area(self.size(), panels)
+    }
+
+    // =========================================================================
+    // ORDEM DE EMPILHAMENTO
+    // =========================================================================
+
+    /// Janelas visíveis de trás para a frente (ordem de pintura): camadas de
+    /// baixo para cima e, dentro de cada camada, pelo z-order.
+    pub fn windows_in_z_order(&self) -> impl Iterator<Item = &Window> + '_ {
+        self.layers
+            .iter_bottom_to_top()
+            .filter_map(|id| self.windows.get(&id.0))
+            .filter(|w| w.is_visible())
+    }
+
+    /// Janelas visíveis da frente para trás (ordem de hit-testing).
+    pub fn windows_in_z_order_top_to_bottom(&self) -> impl Iterator<Item = &Window> + '_ {
+        self.layers
+            .iter_top_to_bottom()
+            .filter_map(|id| self.windows.get(&id.0))
+            .filter(|w| w.is_visible())
+    }
+
+    // =========================================================================
+    // HIT TESTING
+    // =========================================================================
+
+    /// Retorna ID da janela mais ao topo na posição dada (se houver).
+    ///
+    /// Percorre as camadas de cima para baixo (Lock → Overlay → Panel →
+    /// Top → Normal → Background). A camada Cursor nunca recebe clicks.
+    pub fn window_at_point(&self, x: i32, y: i32) -> Option<u32> {
+        self.windows_in_z_order_top_to_bottom()
+            .find(|w| w.layer != LayerType::Cursor && w.accepts_input_at(x, y))
+            .map(|w| w.id.0)
     }
 
     // =========================================================================
     // FOCO
     // =========================================================================
 
+    /// Primeira janela (da frente para trás, minimizadas inclusive) com o
+    /// título exato `title`.
+    pub fn find_window_by_title(&self, title: &str) -> Option<u32> {
+        self.layers
+            .iter_top_to_bottom()
+            .filter_map(|id| self.windows.get(&id.0))
+            .find(|w| w.layer != LayerType::Cursor && w.title == title)
+            .map(|w| w.id.0)
+    }
+
+    /// Janela visível mais à frente nas camadas Normal e Top, exceto
+    /// `exclude` (candidata a receber o foco).
+    pub fn topmost_focusable_window(&self, exclude: u32) -> Option<u32> {
+        self.windows_in_z_order_top_to_bottom()
+            .filter(|w| matches!(w.layer, LayerType::Normal | LayerType::Top))
+            .map(|w| w.id.0)
+            .find(|&id| id != exclude)
+    }
+
     /// Define janela com foco.
     pub fn set_focus(&mut self, id: Option<u32>) {
         if self.focused_window != id {
@@ -296,14 +1065,120 @@ impl RenderEngine {
         }
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Retorna janela com foco.
     #[inline]
     pub fn focused_window(&self) -> Option<u32> {
         self.focused_window
     }
 
+    /// Liga/desliga a verificação de frame idêntico antes de apresentar.
+    pub fn set_skip_identical_frames(&mut self, enabled: bool) {
+        self.skip_identical_frames = enabled;
+    }
+
+    /// Define a curva do fade-in de janelas novas.
+    pub fn set_fade_easing(&mut self, easing: Easing) {
+        self.fade_easing = easing;
+    }
+
+    /// Liga/desliga o fade-in de janelas novas.
+    pub fn set_fade_in(&mut self, enabled: bool) {
+        self.fade_in = enabled;
+        if !enabled {
+            for id in core::mem::take(&mut self.fading_windows).into_keys() {
+                self.mark_damage(id);
+            }
+        }
+    }
+
+    /// Liga/desliga o escurecimento de janelas sem foco.
+    pub fn set_dim_inactive(&mut self, enabled: bool) {
+        if self.dim_inactive != enabled {
+            self.dim_inactive = enabled;
+            self.full_screen_damage();
+        }
+    }
+
+    /// Define o escurecimento por inatividade (alpha do overlay preto).
+    ///
+    /// `u8::MAX` apaga a tela: após apresentar o frame preto, nada mais é
+    /// composto até o nível mudar.
+    pub fn set_idle_dim(&mut self, level: u8) {
+        if self.idle_dim != level {
+            self.idle_dim = level;
+            self.blank_presented = false;
+            self.full_screen_damage();
+        }
+    }
+
+    // =========================================================================
+    // WALLPAPER
+    // =========================================================================
+
+    /// Define a imagem de fundo. Retorna false se o buffer é inválido.
+    pub fn set_wallpaper(&mut self, pixels: Vec<u32>, size: Size) -> bool {
+        if self.safe_mode.is_active() {
+            return false;
+        }
+        match Wallpaper::new(pixels, size) {
+            Some(wallpaper) => {
+                self.wallpaper = Some(wallpaper);
+                self.full_screen_damage();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Copia o buffer da janela (já com conteúdo) para o wallpaper.
+    /// Retorna false se a janela não existe, não tem conteúdo ou o buffer é
+    /// inválido.
+    pub fn set_wallpaper_from_window(&mut self, id: u32) -> bool {
+        let (pixels, size) = match self.windows.get(&id) {
+            Some(w) if w.has_content && !w.pixels().is_empty() => {
+                (w.pixels().to_vec(), w.buffer_size)
+            }
+            _ => return false,
+        };
+        self.set_wallpaper(pixels, size)
+    }
+
+    /// Altera o modo de posicionamento do wallpaper.
+    pub fn set_wallpaper_mode(&mut self, mode: WallpaperMode) {
+        if self.wallpaper_mode != mode {
+            self.wallpaper_mode = mode;
+            if self.wallpaper.is_some() {
+                self.full_screen_damage();
+            }
+        }
+    }
+
+    // =========================================================================
+    // NOTIFICAÇÕES
+    // =========================================================================
+
+    /// Exibe uma nova notificação.
+    pub fn notify(&mut self, toast: Toast) {
+        self.notifications.push(toast);
+        self.damage.add(NotificationManager::bounds(self.size()));
+    }
+
+    /// Avança o relógio das notificações, expirando e animando toasts.
+    pub fn update_notifications(&mut self, now_ms: u64) {
+        self.now_ms = now_ms;
+        if self.notifications.tick(now_ms) {
+            self.damage.add(NotificationManager::bounds(self.size()));
+        }
+    }
+
+    /// Remove e retorna a notificação sob o ponto dado (se houver).
+    pub fn take_notification_at(&mut self, x: i32, y: i32) -> Option<Toast> {
+        let size = self.size();
+        let toast = self.notifications.take_at_point(x, y, size, self.now_ms)?;
+        self.damage.add(NotificationManager::bounds(size));
+        Some(toast)
+    }
+
     // =========================================================================
     // CURSOR
     // =========================================================================
@@ -319,7 +1194,13 @@ impl RenderEngine {
     #[allow(unused)]
     /// Define visibilidade do cursor.
     pub fn set_cursor_visible(&mut self, visible: bool) {
-        self.cursor_visible = visible;
+        if self.cursor_visible != visible {
+            self.cursor_visible = visible;
+            self.damage.add(crate::ui::cursor::bounds(
+                self.cursor_pos.x,
+                self.cursor_pos.y,
+            ));
+        }
     }
 
     // =========================================================================
@@ -327,8 +1208,33 @@ impl RenderEngine {
     // =========================================================================
 
     /// Renderiza um frame com cursor.
-    pub fn render(&mut self, mouse_x: i32, mouse_y: i32) -> SysResult<()> {
-        self.cursor_pos = Point::new(mouse_x, mouse_y);
+    ///
+    /// Apenas a área danificada é recomposta: o fundo é limpo dentro do
+    /// bounding box do dano e somente janelas que o interceptam são
+    /// redesenhadas (recortadas a ele). Dano total recompõe a tela inteira.
+    ///
+    /// Retorna `true` se um frame chegou inteiro ao display.
+    pub fn render(&mut self, mouse_x: i32, mouse_y: i32) -> SysResult<bool> {
+        // Commits recebidos até aqui já geraram damage; o próximo frame recomeça
+        let throttled: BTreeSet<u32> = self
+            .commit_counts
+            .iter()
+            .filter(|(_, &count)| count > COMMIT_THROTTLE_LIMIT)
+            .map(|(&id, _)| id)
+            .collect();
+        self.commit_counts.clear();
+        self.shm_pool.trim(self.now_ms);
+
+        // Callbacks adiados no frame anterior saem agora, com ou sem damage
+        self.frames_done.append(&mut self.deferred_frames);
+
+        self.update_cursor(mouse_x, mouse_y);
+        self.update_fades();
+        if self.splash.update(self.now_ms) {
+            self.full_screen_damage();
+        }
+        #[cfg(feature = "cursor-trail")]
+        self.update_cursor_trail(mouse_x, mouse_y);
         self.frame_count += 1;
 
         // Log periódico
@@ -341,114 +1247,458 @@ impl RenderEngine {
             );
         }
 
-        // 1. Limpar backbuffer
+        // Frame ocioso: desenhar agora os efeitos adiados
+        if !self.damage.has_damage() {
+            for id in core::mem::take(&mut self.deferred_effects) {
+                self.mark_damage(id);
+            }
+        }
+
+        // Nada mudou: manter o frame anterior
+        if !self.damage.has_damage() {
+            return Ok(false);
+        }
+
+        // Tela apagada: o frame preto já está no display
+        if self.blank_presented {
+            self.damage.clear();
+            return Ok(false);
+        }
+
         let size = self.size();
-        Blitter::fill_rect(
-            &mut self.backbuffer,
+        let full = self.damage.is_full_damage();
+        let clip = self.damage.bounding_box();
+
+        // Checksum da região antes de compor (o que está na tela hoje, se o
+        // último present foi completo). Dano total sempre apresenta
+        // (primeiro frame, mudança de modo).
+        let previous_hash = if self.skip_identical_frames && !full && !self.present_incomplete {
+            Some(self.backbuffer.region_hash(clip))
+        } else {
+            None
+        };
+
+        // 1. Limpar área danificada do backbuffer (cor de fundo + wallpaper)
+        let damaged_pixels = visible_area(clip, clip);
+        self.frame_work = damaged_pixels;
+        Blitter::fill_rect(self.backbuffer.pixels_mut(), size, clip, BACKGROUND_COLOR);
+        if let Some(wallpaper) = &self.wallpaper {
+            self.frame_work += damaged_pixels;
+            wallpaper.draw(
+                self.wallpaper_mode,
+                self.backbuffer.pixels_mut(),
+                size,
+                clip,
+            );
+        }
+
+        // 1.1. Tela de abertura (atrás das janelas, some com a primeira)
+        splash::draw(
+            self.backbuffer.pixels_mut(),
             size,
-            Rect::from_size(size),
+            clip,
             BACKGROUND_COLOR,
+            self.splash.opacity(self.now_ms),
         );
 
-        // 2. Coletar janelas para renderizar (ordenadas por layer)
+        // 2. Coletar janelas para renderizar (ordenadas por layer).
+        // O clip já está contido na tela (dano total = tela inteira), então
+        // janelas fora da tela ou fora do dano são descartadas aqui.
         let windows_to_render: Vec<u32> = self
-            .layers
-            .iter_bottom_to_top()
-            .filter(|id| {
-                self.windows
-                    .get(&id.0)
-                    .map(|w| w.is_visible())
-                    .unwrap_or(false)
-            })
-            .map(|id| id.0)
+            .windows_in_z_order()
+            .filter(|w| rects_intersect(paint_bounds(w), clip))
+            .map(|w| w.id.0)
             .collect();
 
-        // 3. Compor janelas
+        // 3. Compor janelas. Os frame callbacks só saem depois que o frame
+        // chega inteiro ao display (passo 8).
+        let mut callbacks = Vec::new();
         for window_id in windows_to_render {
-            self.composite_window(window_id);
+            self.composite_window(window_id, clip);
+            if self.frame_requests.contains(&window_id) {
+                callbacks.push(window_id);
+            }
+        }
+
+        // 4. Desenhar rastro do cursor (diagnóstico)
+        #[cfg(feature = "cursor-trail")]
+        self.cursor_trail
+            .draw(self.backbuffer.pixels_mut(), size, clip);
+
+        // 5. Desenhar notificações (overlay)
+        if !self.notifications.is_empty() {
+            self.notifications
+                .draw(self.backbuffer.pixels_mut(), size, clip, self.now_ms);
         }
 
-        // 4. Desenhar cursor
+        // 5.1. Composição do IME
+        if let Some(preedit) = &self.preedit {
+            preedit.draw(self.backbuffer.pixels_mut(), size, clip);
+        }
+
+        // 5.2. Menu de janela
+        if let Some(menu) = &self.window_menu {
+            menu.draw(self.backbuffer.pixels_mut(), size, clip);
+        }
+
+        // 6. Desenhar cursor: sempre depois de todas as camadas (inclusive
+        // Overlay em tela cheia e Lock), então nunca fica coberto
         if self.cursor_visible {
-            crate::ui::cursor::draw(&mut self.backbuffer, size, mouse_x, mouse_y);
+            crate::ui::cursor::draw(self.backbuffer.pixels_mut(), size, mouse_x, mouse_y, clip);
+        }
+
+        // 6.1. Escurecimento por inatividade (cobre inclusive o cursor)
+        if self.idle_dim > 0 {
+            Blitter::fill_rect_blend(
+                self.backbuffer.pixels_mut(),
+                size,
+                clip,
+                Color((self.idle_dim as u32) << 24),
+            );
+        }
+
+        self.frame_stats = FrameStats {
+            pixels_written: self.frame_work,
+            damaged_pixels,
+        };
+
+        // 7. Apresentar (exceto se a região composta ficou idêntica)
+        let unchanged = previous_hash.is_some_and(|hash| hash == self.backbuffer.region_hash(clip));
+        let presented = !unchanged;
+        if presented {
+            let result = self.present();
+            self.present_incomplete = result.is_err();
+            result?;
+            if let Some(capture) = &mut self.capture {
+                capture.capture(self.backbuffer.pixels());
+            }
         }
 
-        // 5. Apresentar
-        self.present()?;
+        // Tela apagada só conta como apresentada depois de um present
+        // completo (ou de confirmar que a região já estava igual)
+        if self.idle_dim == u8::MAX {
+            self.blank_presented = true;
+        }
 
-        // 6. Limpar damage
+        // 8. Liberar frame callbacks e limpar damage (só depois de
+        // apresentar ou confirmar que a região não mudou)
+        for window_id in callbacks {
+            self.frame_requests.remove(&window_id);
+            if throttled.contains(&window_id) {
+                self.deferred_frames.push(window_id);
+            } else {
+                self.frames_done.push(window_id);
+            }
+        }
         self.damage.clear();
+        for id in core::mem::take(&mut self.dirty_windows) {
+            if let Some(window) = self.windows.get_mut(&id) {
+                window.dirty = false;
+            }
+        }
 
-        Ok(())
+        Ok(presented)
+    }
+
+    /// Avança os fade-ins, danificando as janelas que ainda estão animando.
+    fn update_fades(&mut self) {
+        if self.fading_windows.is_empty() {
+            return;
+        }
+
+        let now = self.now_ms;
+        let ids: Vec<u32> = self.fading_windows.keys().copied().collect();
+        for id in ids {
+            // Danificar também no último passo, para desenhar a opacidade final
+            self.mark_damage(id);
+            if self
+                .fading_windows
+                .get(&id)
+                .is_some_and(|&start| now.saturating_sub(start) >= FADE_IN_MS)
+            {
+                self.fading_windows.remove(&id);
+            }
+        }
+    }
+
+    /// Opacidade efetiva da janela no frame atual (considerando fade-in).
+    fn effective_opacity(&self, window: &Window) -> u8 {
+        match self.fading_windows.get(&window.id.0) {
+            Some(&start) => {
+                let elapsed = self.now_ms.saturating_sub(start);
+                let eased = self.fade_easing.progress(elapsed, FADE_IN_MS);
+                eased.mul_int(window.opacity as i64) as u8
+            }
+            None => window.opacity,
+        }
+    }
+
+    /// Atualiza a posição do cursor, danificando a posição antiga e a nova.
+    fn update_cursor(&mut self, x: i32, y: i32) {
+        if x != self.cursor_pos.x || y != self.cursor_pos.y {
+            self.damage.add(crate::ui::cursor::bounds(
+                self.cursor_pos.x,
+                self.cursor_pos.y,
+            ));
+            self.damage.add(crate::ui::cursor::bounds(x, y));
+            self.cursor_pos = Point::new(x, y);
+        }
+    }
+
+    /// Amostra a posição do cursor no rastro, danificando os pontos.
+    #[cfg(feature = "cursor-trail")]
+    fn update_cursor_trail(&mut self, x: i32, y: i32) {
+        let pos = Point::new(x, y);
+        if self.cursor_trail.is_settled(pos) {
+            return;
+        }
+
+        // Pontos antigos (para apagar) e novos (alpha muda com a idade)
+        for dot in self.cursor_trail.iter() {
+            self.damage.add(CursorTrail::dot_rect(dot));
+        }
+        self.cursor_trail.push(pos);
+        for dot in self.cursor_trail.iter() {
+            self.damage.add(CursorTrail::dot_rect(dot));
+        }
     }
 
-    /// Compõe uma janela no backbuffer.
-    fn composite_window(&mut self, id: u32) {
+    /// Compõe uma janela no backbuffer, limitada a `clip`.
+    fn composite_window(&mut self, id: u32, clip: Rect) {
         let window = match self.windows.get(&id) {
             Some(w) => w,
             None => return,
         };
 
-        let src_pixels = window.pixels();
-        let src_size = window.size;
+        // SHM revogada: não ler, agendar destruição
+        if !window.is_mapped() {
+            if !self.lost_windows.contains(&id) {
+                redpowder::println!("[Render] SHM da janela {} não está mapeada", id);
+                self.lost_windows.push(id);
+            }
+            return;
+        }
+
+        // Totalmente transparente (opacidade 0 ou início do fade-in): nada a
+        // compor, nem sombra/decoração. A janela continua viva.
+        let opacity = self.effective_opacity(window);
+        if opacity == 0 {
+            return;
+        }
+
         let dst_size = self.size();
-        let position = window.position;
 
-        // Desenhar sombra se habilitado
-        if window.has_shadow() {
+        // O conteúdo sempre entra no frame: seu custo conta antes dos efeitos
+        self.frame_work += visible_area(window.rect(), clip);
+        let budget = FRAME_WORK_BUDGET_SCREENS * area_checked(dst_size).unwrap_or(0) as u64;
+        let mut draw_shadow = window.has_shadow() && !self.safe_mode.is_active();
+        if draw_shadow {
+            let cost = visible_area(paint_bounds(window), clip);
+            if self.frame_work + cost > budget {
+                // Frame caro: a sombra fica para um frame ocioso
+                draw_shadow = false;
+                self.deferred_effects.insert(id);
+            } else {
+                self.frame_work += cost;
+            }
+        }
+
+        // Desenhar sombra se habilitado (desligada no modo seguro)
+        if draw_shadow {
             Blitter::draw_shadow(
-                &mut self.backbuffer,
+                self.backbuffer.pixels_mut(),
                 dst_size,
                 window.rect(),
                 SHADOW_OFFSET,
                 SHADOW_BLUR,
                 SHADOW_COLOR,
+                clip,
             );
         }
 
-        // Blit
-        if window.is_transparent() {
-            Blitter::blit_alpha(
-                &mut self.backbuffer,
-                dst_size,
-                src_pixels,
-                src_size,
-                Rect::from_size(src_size),
-                position,
-            );
-        } else {
-            Blitter::blit_opaque(
-                &mut self.backbuffer,
-                dst_size,
-                src_pixels,
-                src_size,
-                Rect::from_size(src_size),
-                position,
-            );
+        // Blit (apenas a parte da janela dentro do clip)
+        blit_window(
+            self.backbuffer.pixels_mut(),
+            dst_size,
+            window,
+            opacity,
+            clip,
+        );
+
+        // Escurecer janela sem foco
+        if self.dim_inactive && window.layer == LayerType::Normal && self.focused_window != Some(id)
+        {
+            if let Some(visible) = clamp_rect(window.rect(), clip) {
+                Blitter::fill_rect_blend(
+                    self.backbuffer.pixels_mut(),
+                    dst_size,
+                    visible,
+                    INACTIVE_DIM_COLOR,
+                );
+            }
         }
 
-        // Indicador de foco (borda colorida)
+        // Indicador de foco (anel em volta do conteúdo)
         if self.focused_window == Some(id) && window.has_decorations() {
-            Blitter::stroke_rect(
-                &mut self.backbuffer,
+            let color = if window.border_color.as_u32() >> 24 != 0 {
+                window.border_color
+            } else {
+                FOCUS_RING_COLOR
+            };
+            draw_focus_ring(
+                self.backbuffer.pixels_mut(),
                 dst_size,
                 window.rect(),
-                2,
-                Color::REDSTONE_ACCENT,
+                color,
+                clip,
             );
         }
     }
 
     /// Envia backbuffer para o display.
+    ///
+    /// Recebe `&self`: a composição do frame já terminou e o backbuffer não
+    /// pode ser alterado até a cópia acabar, então o display sempre recebe
+    /// um frame completo.
+    ///
+    /// Sempre uma cópia para a página visível: `redpowder::graphics` só
+    /// expõe `write_pixels`, sem páginas extras nem syscall de flip/vsync.
+    /// O buffer inteiro é escrito de uma vez no offset 0: `write_pixels` não
+    /// documenta o significado do offset nem do valor de retorno (e o
+    /// `stride` do display pode diferir da largura), então escritas parciais
+    /// (por faixa de linhas ou por região) não são seguras.
     fn present(&self) -> SysResult<()> {
-        let byte_slice = unsafe {
-            core::slice::from_raw_parts(
-                self.backbuffer.as_ptr() as *const u8,
-                self.backbuffer.len() * 4,
-            )
-        };
-
-        write_pixels(0, byte_slice)?;
+        let bytes = self.backbuffer.le_bytes(0, self.backbuffer.byte_len());
+        write_pixels(0, &bytes)?;
         Ok(())
     }
 }
+
+// =============================================================================
+// AUXILIARES
+// =============================================================================
+
+/// Retorna a área de tela afetada pela janela (incluindo sombra).
+///
+/// Usa aritmética saturada: janelas muito fora da tela não estouram.
+fn paint_bounds(window: &Window) -> Rect {
+    let rect = window.rect();
+    if !window.has_shadow() {
+        return rect;
+    }
+
+    let blur = SHADOW_BLUR as i32;
+    let left = (SHADOW_OFFSET.x - blur).min(0);
+    let top = (SHADOW_OFFSET.y - blur).min(0);
+    let right = (SHADOW_OFFSET.x + blur).max(0);
+    let bottom = (SHADOW_OFFSET.y + blur).max(0);
+
+    Rect::new(
+        rect.x.saturating_add(left),
+        rect.y.saturating_add(top),
+        rect.width.saturating_add((right - left) as u32),
+        rect.height.saturating_add((bottom - top) as u32),
+    )
+}
+
+/// Pixels de `rect` dentro de `clip`.
+fn visible_area(rect: Rect, clip: Rect) -> u64 {
+    clamp_rect(rect, clip).map_or(0, |r| r.width as u64 * r.height as u64)
+}
+
+/// Copia o conteúdo visível da janela (dentro de `clip`) para o buffer.
+///
+/// Único ponto que decide entre blit opaco e blit com alpha/opacidade;
+/// qualquer caminho de composição deve passar por aqui. `opacity` é a
+/// opacidade efetiva do frame (pode diferir de `window.opacity` em animações).
+///
+/// Em janelas TRANSPARENT totalmente opacas, a região opaca declarada pelo
+/// cliente é copiada sem blending e apenas o restante passa por alpha.
+fn blit_window(dst: &mut [u32], dst_size: Size, window: &Window, opacity: u8, clip: Rect) {
+    let visible = match clamp_rect(window.content_rect(), clip) {
+        Some(r) => r,
+        None => return,
+    };
+
+    // Cliente sem HiDPI: buffer ampliado (nearest neighbor)
+    if window.scale > 1 {
+        let scaled = window.scaled_buffer_size();
+        Blitter::blit_scaled_opacity(
+            dst,
+            dst_size,
+            Rect::new(
+                window.position.x,
+                window.position.y,
+                scaled.width,
+                scaled.height,
+            ),
+            window.pixels(),
+            window.buffer_size,
+            Rect::from_size(window.buffer_size),
+            visible,
+            opacity,
+        );
+        return;
+    }
+
+    if window.is_transparent() && opacity == 255 {
+        if let Some(region) = window.opaque_region {
+            let opaque = region.offset(window.position.x, window.position.y);
+            if let Some(solid) = clamp_rect(opaque, visible) {
+                blit_window_part(dst, dst_size, window, solid, false, opacity);
+                for rest in subtract_rect(visible, solid) {
+                    blit_window_part(dst, dst_size, window, rest, true, opacity);
+                }
+                return;
+            }
+        }
+    }
+
+    let blend = window.is_transparent() || opacity < 255;
+    blit_window_part(dst, dst_size, window, visible, blend, opacity);
+}
+
+/// Copia a parte `visible` (coordenadas de tela) da janela.
+fn blit_window_part(
+    dst: &mut [u32],
+    dst_size: Size,
+    window: &Window,
+    visible: Rect,
+    blend: bool,
+    opacity: u8,
+) {
+    if visible.is_empty() {
+        return;
+    }
+
+    let src_rect = Rect::new(
+        visible.x - window.position.x,
+        visible.y - window.position.y,
+        visible.width,
+        visible.height,
+    );
+    let dst_point = Point::new(visible.x, visible.y);
+
+    if blend {
+        Blitter::blit_alpha_opacity(
+            dst,
+            dst_size,
+            window.pixels(),
+            window.buffer_size,
+            src_rect,
+            dst_point,
+            visible,
+            opacity,
+        );
+    } else {
+        Blitter::blit_opaque(
+            dst,
+            dst_size,
+            window.pixels(),
+            window.buffer_size,
+            src_rect,
+            dst_point,
+            visible,
+        );
+    }
+}
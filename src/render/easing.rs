@@ -0,0 +1,57 @@
+//! # Easing
+//!
+//! Curvas de easing para animações, em ponto fixo (sem float).
+//!
+//! O progresso `t` vai de 0 a [`Fixed::ONE`]; toda curva satisfaz
+//! `f(0) = 0`, `f(ONE) = ONE` e é monotônica.
+
+use crate::fixed::Fixed;
+
+// =============================================================================
+// EASING
+// =============================================================================
+
+/// Curva de easing de uma animação.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Velocidade constante.
+    #[default]
+    Linear,
+    /// Acelera no início e desacelera no fim (cúbica).
+    EaseInOut,
+    /// Começa rápido e desacelera (cúbica).
+    EaseOutCubic,
+}
+
+impl Easing {
+    /// Aplica a curva a `t` (limitado a `0..=ONE`).
+    pub fn apply(self, t: Fixed) -> Fixed {
+        let one = Fixed::ONE;
+        let t = t.clamp(Fixed::ZERO, one);
+        let cube = |x: Fixed| x.mul(x).mul(x);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseInOut => {
+                if t < Fixed::HALF {
+                    // 4t³
+                    Fixed(cube(t).0 * 4)
+                } else {
+                    // 1 - (2 - 2t)³ / 2
+                    let u = Fixed((one - t).0 * 2);
+                    one - Fixed(cube(u).0 / 2)
+                }
+            }
+            // 1 - (1 - t)³
+            Self::EaseOutCubic => one - cube(one - t),
+        }
+    }
+
+    /// Progresso com easing de uma animação de `duration_ms` após `elapsed_ms`.
+    pub fn progress(self, elapsed_ms: u64, duration_ms: u64) -> Fixed {
+        if duration_ms == 0 || elapsed_ms >= duration_ms {
+            return Fixed::ONE;
+        }
+        self.apply(Fixed::from_ratio(elapsed_ms as i64, duration_ms as i64))
+    }
+}
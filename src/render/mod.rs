@@ -4,11 +4,23 @@
 //!
 //! ## Componentes
 //!
+//! - **Backbuffer**: Buffer de composição em RAM
 //! - **Blitter**: Operações de cópia de pixels otimizadas
+//! - **Capture**: Cópia dos frames para gravação de tela
+//! - **Easing**: Curvas de animação em ponto fixo
 //! - **RenderEngine**: Motor de composição principal
+//! - **SelfTest**: Padrões de diagnóstico do display (feature `selftest`)
+//! - **Wallpaper**: Imagem de fundo e modos de posicionamento
 
+pub mod backbuffer;
 pub mod blitter;
+pub mod capture;
 pub mod compositor;
+pub mod easing;
+#[cfg(feature = "selftest")]
+pub mod selftest;
+pub mod shm_pool;
+pub mod wallpaper;
 
 pub use blitter::Blitter;
 pub use compositor::RenderEngine;
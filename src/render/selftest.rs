@@ -0,0 +1,162 @@
+//! # Self-test
+//!
+//! Padrões conhecidos desenhados direto no backbuffer, sem janelas de
+//! clientes, para diagnosticar o display em hardware novo: barras de cor
+//! (fill), um gradiente (blit) e um bloco em movimento (present).
+//!
+//! Se os padrões aparecem certos no backbuffer mas errados na tela, o
+//! problema está no framebuffer do kernel, não no compositor.
+
+use alloc::vec::Vec;
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use super::Blitter;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Barras de cor (padrão SMPTE simplificado).
+const BAR_COLORS: [Color; 8] = [
+    Color(0xFFFFFFFF),
+    Color(0xFFFFFF00),
+    Color(0xFF00FFFF),
+    Color(0xFF00FF00),
+    Color(0xFFFF00FF),
+    Color(0xFFFF0000),
+    Color(0xFF0000FF),
+    Color(0xFF000000),
+];
+
+/// Lado do bloco em movimento (px).
+const BLOCK_SIZE: u32 = 64;
+
+/// Cor do bloco em movimento.
+const BLOCK_COLOR: Color = Color(0xFFFF8000);
+
+/// Frames do bloco em movimento.
+pub const BLOCK_FRAMES: u32 = 30;
+
+/// Intervalo entre os frames do bloco (ms).
+pub const BLOCK_FRAME_MS: u64 = 16;
+
+// =============================================================================
+// RESULTADO
+// =============================================================================
+
+/// Resultado das operações testadas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// As barras preenchidas têm as cores esperadas.
+    pub fill: bool,
+    /// O gradiente copiado é idêntico à origem.
+    pub blit: bool,
+    /// O display aceitou todos os frames.
+    pub present: bool,
+}
+
+// =============================================================================
+// PADRÕES
+// =============================================================================
+
+/// Faixa das barras de cor: os dois terços superiores da tela.
+fn bars_rect(size: Size) -> Rect {
+    Rect::new(0, 0, size.width, size.height * 2 / 3)
+}
+
+/// Faixa do gradiente: o terço inferior da tela.
+fn gradient_rect(size: Size) -> Rect {
+    let top = size.height * 2 / 3;
+    Rect::new(0, top as i32, size.width, size.height - top)
+}
+
+/// Retângulo da barra `index`.
+fn bar_rect(size: Size, index: usize) -> Rect {
+    let bars = bars_rect(size);
+    let count = BAR_COLORS.len() as u32;
+    let x0 = size.width * index as u32 / count;
+    let x1 = size.width * (index as u32 + 1) / count;
+    Rect::new(x0 as i32, 0, x1 - x0, bars.height)
+}
+
+/// Desenha as barras de cor.
+pub fn draw_color_bars(buffer: &mut [u32], size: Size) {
+    for (index, &color) in BAR_COLORS.iter().enumerate() {
+        Blitter::fill_rect(buffer, size, bar_rect(size, index), color);
+    }
+}
+
+/// Confere o pixel central de cada barra.
+pub fn check_color_bars(buffer: &[u32], size: Size) -> bool {
+    BAR_COLORS.iter().enumerate().all(|(index, &color)| {
+        let bar = bar_rect(size, index);
+        let center = Point::new(bar.x + bar.width as i32 / 2, bar.y + bar.height as i32 / 2);
+        pixel_at(buffer, size, center) == Some(color.as_u32())
+    })
+}
+
+/// Gradiente horizontal de preto a branco, do tamanho de `rect`.
+fn gradient_source(rect: Rect) -> Vec<u32> {
+    let width = rect.width.max(1);
+    let mut pixels = Vec::with_capacity(rect.width as usize * rect.height as usize);
+    for _ in 0..rect.height {
+        for x in 0..rect.width {
+            let level = x * 255 / width;
+            pixels.push(0xFF00_0000 | (level << 16) | (level << 8) | level);
+        }
+    }
+    pixels
+}
+
+/// Copia o gradiente para o terço inferior e confere a cópia contra a
+/// origem. Retorna true se forem idênticas.
+pub fn blit_gradient(buffer: &mut [u32], size: Size) -> bool {
+    let rect = gradient_rect(size);
+    let source = gradient_source(rect);
+    let src_size = Size::new(rect.width, rect.height);
+    Blitter::blit_opaque(
+        buffer,
+        size,
+        &source,
+        src_size,
+        Rect::from_size(src_size),
+        Point::new(rect.x, rect.y),
+        rect,
+    );
+
+    let width = size.width as usize;
+    source
+        .chunks(rect.width.max(1) as usize)
+        .enumerate()
+        .all(|(row, expected)| {
+            let start = (rect.y as usize + row) * width;
+            buffer.get(start..start + expected.len()) == Some(expected)
+        })
+}
+
+/// Redesenha as barras com o bloco na posição do `frame`.
+pub fn draw_moving_block(buffer: &mut [u32], size: Size, frame: u32) {
+    draw_color_bars(buffer, size);
+
+    let bars = bars_rect(size);
+    let travel = size.width.saturating_sub(BLOCK_SIZE);
+    let x = travel * frame / BLOCK_FRAMES.max(1);
+    let y = bars.height.saturating_sub(BLOCK_SIZE) / 2;
+    Blitter::fill_rect(
+        buffer,
+        size,
+        Rect::new(x as i32, y as i32, BLOCK_SIZE, BLOCK_SIZE),
+        BLOCK_COLOR,
+    );
+}
+
+/// Pixel em `point`, se estiver dentro do buffer.
+fn pixel_at(buffer: &[u32], size: Size, point: Point) -> Option<u32> {
+    if point.x < 0 || point.y < 0 || point.x as u32 >= size.width {
+        return None;
+    }
+    buffer
+        .get(point.y as usize * size.width as usize + point.x as usize)
+        .copied()
+}
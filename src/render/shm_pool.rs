@@ -0,0 +1,112 @@
+//! # SHM Pool
+//!
+//! Reaproveitamento de buffers SHM de janelas destruídas.
+//!
+//! Os tamanhos são arredondados para classes (no máximo 1/8 da potência de
+//! dois seguinte acima do pedido), então janelas de tamanhos parecidos
+//! compartilham a mesma classe. Buffers parados por mais de
+//! `SHM_POOL_IDLE_MS` são liberados.
+//!
+//! O cliente dono de uma janela destruída continua com a SHM mapeada, então
+//! um buffer só volta para uma janela nova do mesmo cliente (identificado
+//! pela porta de resposta, como nas cotas). Entregá-lo a outro cliente
+//! deixaria o dono antigo ler e escrever na janela nova.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use redpowder::ipc::SharedMemory;
+use redpowder::syscall::SysResult;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Menor granularidade de classe (64 KiB).
+const MIN_CLASS_STEP: usize = 64 * 1024;
+
+/// Máximo de buffers guardados.
+const MAX_POOLED_BUFFERS: usize = 8;
+
+/// Tempo parado até o buffer ser liberado (ms).
+const SHM_POOL_IDLE_MS: u64 = 5_000;
+
+// =============================================================================
+// SHM POOL
+// =============================================================================
+
+/// Buffer livre no pool.
+struct PooledBuffer {
+    shm: SharedMemory,
+    /// Cliente que ainda mapeia o buffer.
+    owner: String,
+    /// Momento em que voltou ao pool (ms).
+    freed_at: u64,
+}
+
+/// Pool de buffers SHM livres.
+pub struct ShmPool {
+    free: Vec<PooledBuffer>,
+}
+
+impl ShmPool {
+    /// Cria pool vazio.
+    pub fn new() -> Self {
+        Self { free: Vec::new() }
+    }
+
+    /// Tamanho da classe de `bytes`.
+    pub fn size_class(bytes: usize) -> usize {
+        let step = (bytes.next_power_of_two() / 8).max(MIN_CLASS_STEP);
+        bytes.div_ceil(step).saturating_mul(step)
+    }
+
+    /// Retorna um buffer de pelo menos `bytes` para `owner`, reaproveitado
+    /// da mesma classe e do mesmo cliente ou recém-criado. Sem `owner`
+    /// (cliente sem porta de resposta), sempre cria.
+    pub fn allocate(&mut self, bytes: usize, owner: &str) -> SysResult<SharedMemory> {
+        let class = Self::size_class(bytes);
+        if !owner.is_empty() {
+            if let Some(index) = self
+                .free
+                .iter()
+                .position(|b| b.shm.size() == class && b.owner == owner)
+            {
+                return Ok(self.free.swap_remove(index).shm);
+            }
+        }
+        SharedMemory::create(class)
+    }
+
+    /// Devolve ao pool um buffer de `owner` (o mais antigo sai se estiver
+    /// cheio). Buffers sem dono conhecido são liberados.
+    pub fn recycle(&mut self, shm: SharedMemory, owner: &str, now_ms: u64) {
+        if shm.as_ptr().is_null() || owner.is_empty() {
+            return;
+        }
+        if self.free.len() >= MAX_POOLED_BUFFERS {
+            self.free.remove(0);
+        }
+        self.free.push(PooledBuffer {
+            shm,
+            owner: String::from(owner),
+            freed_at: now_ms,
+        });
+    }
+
+    /// Libera buffers parados há mais de `SHM_POOL_IDLE_MS`.
+    pub fn trim(&mut self, now_ms: u64) {
+        self.free
+            .retain(|b| now_ms.saturating_sub(b.freed_at) < SHM_POOL_IDLE_MS);
+    }
+
+    /// Libera todos os buffers (ex.: pouca memória).
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+}
+
+impl Default for ShmPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
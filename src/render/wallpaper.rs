@@ -0,0 +1,157 @@
+//! # Wallpaper
+//!
+//! Imagem de fundo desenhada antes das janelas, com modos de
+//! posicionamento (centralizar, ladrilhar, esticar, preencher).
+
+use alloc::vec::Vec;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use super::blitter::Blitter;
+use crate::scene::clamp_rect;
+
+// =============================================================================
+// MODO
+// =============================================================================
+
+/// Como o wallpaper é posicionado na tela.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WallpaperMode {
+    /// Tamanho original, centralizado (cor de fundo ao redor).
+    Center,
+    /// Repetido a partir da origem.
+    Tile,
+    /// Escalado para a tela inteira (ignora proporção).
+    #[default]
+    Stretch,
+    /// Escalado preservando proporção até cobrir a tela (excesso cortado).
+    Fill,
+}
+
+impl WallpaperMode {
+    /// Retângulo de destino da imagem na tela.
+    ///
+    /// Pode ultrapassar a tela (origem negativa em `Center`/`Fill`).
+    /// Em `Tile` retorna o primeiro ladrilho, na origem.
+    pub fn dest_rect(self, image: Size, screen: Size) -> Rect {
+        match self {
+            Self::Center => Rect::new(
+                (screen.width as i32 - image.width as i32) / 2,
+                (screen.height as i32 - image.height as i32) / 2,
+                image.width,
+                image.height,
+            ),
+            Self::Tile => Rect::new(0, 0, image.width, image.height),
+            Self::Stretch => Rect::from_size(screen),
+            Self::Fill => {
+                if image.width == 0 || image.height == 0 {
+                    return Rect::ZERO;
+                }
+
+                let sw = screen.width as u64;
+                let sh = screen.height as u64;
+                let iw = image.width as u64;
+                let ih = image.height as u64;
+
+                // Escala = max(sw/iw, sh/ih), arredondando para cima
+                let (width, height) = if sw * ih >= sh * iw {
+                    (sw, (ih * sw).div_ceil(iw))
+                } else {
+                    ((iw * sh).div_ceil(ih), sh)
+                };
+
+                Rect::new(
+                    ((sw as i64 - width as i64) / 2) as i32,
+                    ((sh as i64 - height as i64) / 2) as i32,
+                    width as u32,
+                    height as u32,
+                )
+            }
+        }
+    }
+}
+
+// =============================================================================
+// WALLPAPER
+// =============================================================================
+
+/// Imagem de fundo (ARGB8888).
+pub struct Wallpaper {
+    /// Pixels em ordem de linhas.
+    pixels: Vec<u32>,
+    /// Dimensões da imagem.
+    size: Size,
+}
+
+impl Wallpaper {
+    /// Cria wallpaper. Retorna None se `pixels` não corresponde a `size`.
+    pub fn new(pixels: Vec<u32>, size: Size) -> Option<Self> {
+        let count = (size.width as usize).checked_mul(size.height as usize)?;
+        if count == 0 || pixels.len() < count {
+            return None;
+        }
+        Some(Self { pixels, size })
+    }
+
+    /// Desenha o wallpaper no modo dado, apenas dentro de `clip`.
+    ///
+    /// Não limpa o fundo: o chamador preenche a cor de fundo antes.
+    pub fn draw(&self, mode: WallpaperMode, dst: &mut [u32], dst_size: Size, clip: Rect) {
+        let dest = mode.dest_rect(self.size, dst_size);
+
+        match mode {
+            WallpaperMode::Center => self.blit_at(dst, dst_size, dest, clip),
+            WallpaperMode::Tile => {
+                let tile_w = self.size.width as i32;
+                let tile_h = self.size.height as i32;
+
+                // Primeiro ladrilho que toca o clip
+                let mut y = clip.y.div_euclid(tile_h) * tile_h;
+                while y < clip.bottom() {
+                    let mut x = clip.x.div_euclid(tile_w) * tile_w;
+                    while x < clip.right() {
+                        let tile = Rect::new(x, y, self.size.width, self.size.height);
+                        self.blit_at(dst, dst_size, tile, clip);
+                        x += tile_w;
+                    }
+                    y += tile_h;
+                }
+            }
+            WallpaperMode::Stretch | WallpaperMode::Fill => {
+                Blitter::blit_scaled_clipped(
+                    dst,
+                    dst_size,
+                    dest,
+                    &self.pixels,
+                    self.size,
+                    Rect::from_size(self.size),
+                    clip,
+                );
+            }
+        }
+    }
+
+    /// Copia a imagem em tamanho original para `dest`, limitada a `clip`.
+    fn blit_at(&self, dst: &mut [u32], dst_size: Size, dest: Rect, clip: Rect) {
+        let visible = match clamp_rect(dest, clip) {
+            Some(r) => r,
+            None => return,
+        };
+
+        let src_rect = Rect::new(
+            visible.x - dest.x,
+            visible.y - dest.y,
+            visible.width,
+            visible.height,
+        );
+
+        Blitter::blit_opaque(
+            dst,
+            dst_size,
+            &self.pixels,
+            self.size,
+            src_rect,
+            Point::new(visible.x, visible.y),
+            clip,
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! Sistema de rastreamento de áreas danificadas para otimização de renderização.
 
 use alloc::vec::Vec;
-use gfx_types::geometry::Rect;
+use gfx_types::geometry::{Rect, Size};
 
 // =============================================================================
 // DAMAGE TRACKER
@@ -54,12 +54,15 @@ impl DamageTracker {
 
     /// Adiciona região danificada.
     pub fn add(&mut self, rect: Rect) {
+        // Bordas que estouram `i32` fariam `union`/`intersects` verem o
+        // retângulo invertido
+        let rect = normalize_rect(rect);
         if rect.is_empty() {
             return;
         }
 
         // Clip à tela
-        let clipped = match rect.intersection(&self.screen_rect) {
+        let clipped = match clamp_rect(rect, self.screen_rect) {
             Some(r) => r,
             None => return,
         };
@@ -74,9 +77,9 @@ impl DamageTracker {
 
         self.regions.push(clipped);
 
-        // Colapsar se muitas regiões
+        // Fundir regiões se passar do orçamento
         if self.regions.len() > self.max_regions {
-            self.collapse();
+            self.merge_to_budget();
         }
     }
 
@@ -87,16 +90,12 @@ impl DamageTracker {
         self.regions.clear();
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Retorna se há alguma região danificada.
     #[inline]
     pub fn has_damage(&self) -> bool {
         self.full_damage || !self.regions.is_empty()
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Retorna se é dano total.
     #[inline]
     pub fn is_full_damage(&self) -> bool {
@@ -133,6 +132,39 @@ impl DamageTracker {
         self.full_damage = false;
     }
 
+    /// Reduz as regiões até `max_regions`.
+    ///
+    /// Funde repetidamente o par cuja união acrescenta a menor área extra,
+    /// preservando a granularidade do dano. Colapsar tudo em um único
+    /// bounding box fica como último recurso (orçamento de uma região).
+    fn merge_to_budget(&mut self) {
+        if self.max_regions <= 1 {
+            self.collapse();
+            return;
+        }
+
+        while self.regions.len() > self.max_regions {
+            let mut best = (0, 1);
+            let mut best_cost = i64::MAX;
+
+            for i in 0..self.regions.len() {
+                for j in (i + 1)..self.regions.len() {
+                    let a = &self.regions[i];
+                    let b = &self.regions[j];
+                    let cost = area(&a.union(b)) - area(a) - area(b);
+                    if cost < best_cost {
+                        best_cost = cost;
+                        best = (i, j);
+                    }
+                }
+            }
+
+            let (i, j) = best;
+            self.regions[i] = self.regions[i].union(&self.regions[j]);
+            self.regions.swap_remove(j);
+        }
+    }
+
     /// Colapsa todas as regiões em uma só.
     fn collapse(&mut self) {
         if self.regions.len() <= 1 {
@@ -163,3 +195,115 @@ impl Default for DamageTracker {
         Self::new()
     }
 }
+
+// =============================================================================
+// AUXILIARES
+// =============================================================================
+
+/// Recorta `rect` aos limites de `bounds`.
+///
+/// As bordas são calculadas em `i64`, então retângulos com origem negativa
+/// ou que se estendem além de `i32::MAX` (janelas arrastadas para fora da
+/// tela) nunca produzem resultados fora de `bounds`.
+pub fn clamp_rect(rect: Rect, bounds: Rect) -> Option<Rect> {
+    let x0 = (rect.x as i64).max(bounds.x as i64);
+    let y0 = (rect.y as i64).max(bounds.y as i64);
+    let x1 = (rect.x as i64 + rect.width as i64).min(bounds.x as i64 + bounds.width as i64);
+    let y1 = (rect.y as i64 + rect.height as i64).min(bounds.y as i64 + bounds.height as i64);
+
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+
+    Some(Rect::new(
+        x0 as i32,
+        y0 as i32,
+        (x1 - x0) as u32,
+        (y1 - y0) as u32,
+    ))
+}
+
+/// Retângulo entre os cantos (x0, y0) e (x1, y1), em qualquer ordem: a
+/// origem vira o canto mínimo e o tamanho nunca é negativo. Os cantos são
+/// limitados ao alcance de `i32`.
+pub fn rect_from_corners(x0: i64, y0: i64, x1: i64, y1: i64) -> Rect {
+    let clamp = |v: i64| v.clamp(i32::MIN as i64, i32::MAX as i64);
+    let (x0, x1) = (clamp(x0.min(x1)), clamp(x0.max(x1)));
+    let (y0, y1) = (clamp(y0.min(y1)), clamp(y0.max(y1)));
+    Rect::new(x0 as i32, y0 as i32, (x1 - x0) as u32, (y1 - y0) as u32)
+}
+
+/// Normaliza `rect` para que a borda direita/inferior caiba em `i32`
+/// (o tamanho é reduzido se `x + width` estouraria). Assim os métodos de
+/// `Rect`, que calculam as bordas em `i32`, nunca veem bordas invertidas.
+pub fn normalize_rect(rect: Rect) -> Rect {
+    rect_from_corners(
+        rect.x as i64,
+        rect.y as i64,
+        rect.x as i64 + rect.width as i64,
+        rect.y as i64 + rect.height as i64,
+    )
+}
+
+/// Partes de `rect` fora de `hole`: faixas superior, inferior, esquerda e
+/// direita (vazias quando não existem).
+pub fn subtract_rect(rect: Rect, hole: Rect) -> [Rect; 4] {
+    let hole = match clamp_rect(hole, rect) {
+        Some(h) => h,
+        None => return [rect, Rect::ZERO, Rect::ZERO, Rect::ZERO],
+    };
+
+    let top_h = (hole.y - rect.y) as u32;
+    let bottom_y = hole.y + hole.height as i32;
+    let bottom_h = (rect.y + rect.height as i32 - bottom_y) as u32;
+    let left_w = (hole.x - rect.x) as u32;
+    let right_x = hole.x + hole.width as i32;
+    let right_w = (rect.x + rect.width as i32 - right_x) as u32;
+
+    [
+        Rect::new(rect.x, rect.y, rect.width, top_h),
+        Rect::new(rect.x, bottom_y, rect.width, bottom_h),
+        Rect::new(rect.x, hole.y, left_w, hole.height),
+        Rect::new(right_x, hole.y, right_w, hole.height),
+    ]
+}
+
+/// Verifica se o ponto está dentro de `rect` (bordas em `i64`, como em
+/// [`clamp_rect`]).
+pub fn rect_contains(rect: Rect, x: i32, y: i32) -> bool {
+    let (x, y) = (x as i64, y as i64);
+    x >= rect.x as i64
+        && y >= rect.y as i64
+        && x < rect.x as i64 + rect.width as i64
+        && y < rect.y as i64 + rect.height as i64
+}
+
+/// Recorta `rect` à tela `screen` (origem em 0, 0).
+#[inline]
+pub fn clamp_to_screen(rect: Rect, screen: Size) -> Option<Rect> {
+    clamp_rect(rect, Rect::new(0, 0, screen.width, screen.height))
+}
+
+/// Número de pixels de `size`, ou `None` se não couber em `usize`.
+#[inline]
+pub fn area_checked(size: Size) -> Option<usize> {
+    (size.width as usize).checked_mul(size.height as usize)
+}
+
+/// Bytes de um buffer ARGB32 de `size`, ou `None` em overflow.
+#[inline]
+pub fn buffer_bytes(size: Size) -> Option<usize> {
+    area_checked(size)?.checked_mul(4)
+}
+
+/// Verifica se dois retângulos se sobrepõem (origens negativas permitidas).
+#[inline]
+pub fn rects_intersect(a: Rect, b: Rect) -> bool {
+    clamp_rect(a, b).is_some()
+}
+
+/// Área de um retângulo.
+#[inline]
+fn area(rect: &Rect) -> i64 {
+    rect.width as i64 * rect.height as i64
+}
@@ -63,14 +63,18 @@ impl Layer {
         }
     }
 
-    /// Retorna janelas de baixo para cima.
+    /// Retorna janelas de baixo para cima (nenhuma se a camada está oculta).
     pub fn iter_bottom_to_top(&self) -> impl Iterator<Item = WindowId> + '_ {
-        self.windows.iter().copied()
+        self.windows.iter().copied().filter(move |_| self.visible)
     }
 
-    /// Retorna janelas de cima para baixo.
+    /// Retorna janelas de cima para baixo (nenhuma se a camada está oculta).
     pub fn iter_top_to_bottom(&self) -> impl Iterator<Item = WindowId> + '_ {
-        self.windows.iter().rev().copied()
+        self.windows
+            .iter()
+            .rev()
+            .copied()
+            .filter(move |_| self.visible)
     }
 
     // TODO: Revisar no futuro
@@ -89,8 +93,6 @@ impl Layer {
         self.windows.is_empty()
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Contém janela?
     #[inline]
     pub fn contains(&self, id: WindowId) -> bool {
@@ -106,6 +108,9 @@ impl Layer {
 pub struct LayerManager {
     /// Camada de background (wallpaper, desktop icons).
     background: Layer,
+    /// Widgets de desktop (relógio, monitor): janelas `Background` acima do
+    /// wallpaper e abaixo das janelas normais, com ordem própria.
+    desktop_widgets: Layer,
     /// Camada normal (janelas de aplicações).
     normal: Layer,
     /// Camada top (always on top).
@@ -125,6 +130,7 @@ impl LayerManager {
     pub fn new() -> Self {
         Self {
             background: Layer::new(LayerType::Background),
+            desktop_widgets: Layer::new(LayerType::Background),
             normal: Layer::new(LayerType::Normal),
             top: Layer::new(LayerType::Top),
             panel: Layer::new(LayerType::Panel),
@@ -162,6 +168,27 @@ impl LayerManager {
         }
     }
 
+    /// Camada onde está a janela `id` de `layer_type` (a subcamada de
+    /// widgets, se for um widget de desktop).
+    pub fn layer_of_mut(&mut self, id: WindowId, layer_type: LayerType) -> &mut Layer {
+        if layer_type == LayerType::Background && self.desktop_widgets.contains(id) {
+            return &mut self.desktop_widgets;
+        }
+        self.get_mut(layer_type)
+    }
+
+    /// Move uma janela `Background` para a subcamada de widgets (no topo
+    /// dela) ou de volta para o fundo.
+    pub fn set_desktop_widget(&mut self, id: WindowId, widget: bool) {
+        self.background.remove_window(id);
+        self.desktop_widgets.remove_window(id);
+        if widget {
+            self.desktop_widgets.add_window(id);
+        } else {
+            self.background.add_window(id);
+        }
+    }
+
     /// Adiciona janela a uma camada.
     pub fn add_window_to_layer(&mut self, id: WindowId, layer_type: LayerType) {
         self.get_mut(layer_type).add_window(id);
@@ -170,6 +197,7 @@ impl LayerManager {
     /// Remove janela de qualquer camada.
     pub fn remove_window(&mut self, id: WindowId) {
         self.background.remove_window(id);
+        self.desktop_widgets.remove_window(id);
         self.normal.remove_window(id);
         self.top.remove_window(id);
         self.panel.remove_window(id);
@@ -178,11 +206,9 @@ impl LayerManager {
         self.cursor.remove_window(id);
     }
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Move janela entre camadas.
     pub fn move_window(&mut self, id: WindowId, from: LayerType, to: LayerType) {
-        self.get_mut(from).remove_window(id);
+        self.layer_of_mut(id, from).remove_window(id);
         self.get_mut(to).add_window(id);
     }
 
@@ -190,6 +216,7 @@ impl LayerManager {
     pub fn iter_bottom_to_top(&self) -> impl Iterator<Item = WindowId> + '_ {
         self.background
             .iter_bottom_to_top()
+            .chain(self.desktop_widgets.iter_bottom_to_top())
             .chain(self.normal.iter_bottom_to_top())
             .chain(self.top.iter_bottom_to_top())
             .chain(self.panel.iter_bottom_to_top())
@@ -207,6 +234,7 @@ impl LayerManager {
             .chain(self.panel.iter_top_to_bottom())
             .chain(self.top.iter_top_to_bottom())
             .chain(self.normal.iter_top_to_bottom())
+            .chain(self.desktop_widgets.iter_top_to_bottom())
             .chain(self.background.iter_top_to_bottom())
     }
 
@@ -215,6 +243,7 @@ impl LayerManager {
     /// Total de janelas em todas as camadas.
     pub fn total_windows(&self) -> usize {
         self.background.len()
+            + self.desktop_widgets.len()
             + self.normal.len()
             + self.top.len()
             + self.panel.len()
@@ -7,13 +7,18 @@
 //! - **Window**: Janela de aplicação com estado completo
 //! - **Layer**: Camadas de composição (background, normal, panel, overlay)
 //! - **Damage**: Rastreamento de áreas modificadas
+//! - **Placement**: Posicionamento de popups relativo à janela pai
 
 pub mod damage;
 pub mod layer;
+pub mod placement;
 pub mod window;
 
-pub use damage::DamageTracker;
+pub use damage::{
+    area_checked, buffer_bytes, clamp_rect, clamp_to_screen, rects_intersect, subtract_rect,
+    DamageTracker,
+};
 // TODO: Revisar no futuro
 #[allow(unused)]
 pub use layer::{Layer, LayerManager};
-pub use window::{Window, WindowId};
+pub use window::{FullscreenRestore, Window, WindowId, WindowIdAllocator, WindowType};
@@ -0,0 +1,197 @@
+//! # Scene - Placement
+//!
+//! Posicionamento de popups/menus relativo a uma janela pai, limites de
+//! arraste de janelas e bordas reservadas por painéis.
+
+use gfx_types::geometry::{Point, Rect, Size};
+
+// =============================================================================
+// GRAVITY
+// =============================================================================
+
+/// Qual canto do popup fica preso ao ponto de ancoragem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gravity {
+    /// Canto superior esquerdo no anchor (cresce para direita/baixo).
+    TopLeft,
+    /// Canto superior direito no anchor (cresce para esquerda/baixo).
+    TopRight,
+    /// Canto inferior esquerdo no anchor (cresce para direita/cima).
+    BottomLeft,
+    /// Canto inferior direito no anchor (cresce para esquerda/cima).
+    BottomRight,
+}
+
+impl Gravity {
+    /// Popup cresce para a direita do anchor.
+    #[inline]
+    fn grows_right(self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+
+    /// Popup cresce para baixo do anchor.
+    #[inline]
+    fn grows_down(self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+}
+
+// =============================================================================
+// POSICIONAMENTO
+// =============================================================================
+
+/// Calcula a posição de um popup ancorado em `anchor` (relativo a `parent`).
+///
+/// Se o popup ultrapassar uma borda da tela, é espelhado para o outro lado
+/// do anchor naquele eixo; se ainda assim não couber, é empurrado para
+/// dentro da tela.
+pub fn place_relative(
+    parent: Rect,
+    anchor: Point,
+    size: Size,
+    gravity: Gravity,
+    screen: Rect,
+) -> Point {
+    let anchor_x = parent.x.saturating_add(anchor.x);
+    let anchor_y = parent.y.saturating_add(anchor.y);
+
+    Point::new(
+        place_axis(
+            anchor_x,
+            size.width as i32,
+            gravity.grows_right(),
+            screen.x,
+            screen.right(),
+        ),
+        place_axis(
+            anchor_y,
+            size.height as i32,
+            gravity.grows_down(),
+            screen.y,
+            screen.bottom(),
+        ),
+    )
+}
+
+/// Posiciona um eixo: `forward` = cresce no sentido positivo a partir do anchor.
+fn place_axis(anchor: i32, len: i32, forward: bool, min: i32, max: i32) -> i32 {
+    let start = |forward: bool| {
+        if forward {
+            anchor
+        } else {
+            anchor.saturating_sub(len)
+        }
+    };
+    let fits = |pos: i32| pos >= min && pos.saturating_add(len) <= max;
+
+    let preferred = start(forward);
+    if fits(preferred) {
+        return preferred;
+    }
+
+    let flipped = start(!forward);
+    if fits(flipped) {
+        return flipped;
+    }
+
+    // Não cabe em nenhum lado: empurrar para dentro (prioriza o início)
+    preferred.min(max.saturating_sub(len)).max(min)
+}
+
+/// Limita a posição de uma janela arrastada para que a barra de título
+/// continue alcançável.
+///
+/// Pelo menos `min_visible` pixels da largura ficam dentro de `area` na
+/// horizontal; na vertical, o topo não sobe acima de `area` e ao menos
+/// `min_visible` pixels (até a altura da barra) ficam acima da borda de baixo.
+pub fn keep_titlebar_reachable(
+    position: Point,
+    size: Size,
+    titlebar_height: u32,
+    area: Rect,
+    min_visible: u32,
+) -> Point {
+    let min_x = min_visible.min(size.width) as i64;
+    let min_y = min_visible.min(titlebar_height).min(size.height) as i64;
+
+    let x = (position.x as i64)
+        .max(area.x as i64 + min_x - size.width as i64)
+        .min(area.x as i64 + area.width as i64 - min_x);
+    let y = (position.y as i64)
+        .min(area.y as i64 + area.height as i64 - min_y)
+        .max(area.y as i64);
+
+    Point::new(x as i32, y as i32)
+}
+
+// =============================================================================
+// PAINÉIS
+// =============================================================================
+
+/// Borda da tela ocupada por um painel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Borda da tela em que `rect` se encaixa como painel.
+///
+/// Painéis horizontais ocupam a largura toda no topo ou na base; verticais,
+/// a altura toda à esquerda ou à direita. Só esses reservam faixa da área
+/// de trabalho. Uma janela que cobre a tela inteira não é faixa de borda.
+pub fn panel_edge(rect: Rect, screen: Size) -> Option<ScreenEdge> {
+    let bottom = rect.y as i64 + rect.height as i64;
+    let right = rect.x as i64 + rect.width as i64;
+
+    let full_width = rect.x <= 0 && right >= screen.width as i64;
+    let full_height = rect.y <= 0 && bottom >= screen.height as i64;
+    if full_width && full_height {
+        return None;
+    }
+
+    if full_width {
+        if rect.y == 0 {
+            return Some(ScreenEdge::Top);
+        }
+        if bottom == screen.height as i64 {
+            return Some(ScreenEdge::Bottom);
+        }
+    }
+    if full_height {
+        if rect.x == 0 {
+            return Some(ScreenEdge::Left);
+        }
+        if right == screen.width as i64 {
+            return Some(ScreenEdge::Right);
+        }
+    }
+    None
+}
+
+/// Área de trabalho: `screen` sem as faixas reservadas pelos painéis.
+///
+/// Cada painel que ocupa uma borda inteira (ver [`panel_edge`]) reserva nela
+/// a espessura perpendicular; os demais não reservam nada.
+pub fn work_area(screen: Size, panels: impl Iterator<Item = Rect>) -> Rect {
+    let (mut top, mut bottom, mut left, mut right) = (0u32, 0u32, 0u32, 0u32);
+
+    for rect in panels {
+        match panel_edge(rect, screen) {
+            Some(ScreenEdge::Top) => top = top.max(rect.height),
+            Some(ScreenEdge::Bottom) => bottom = bottom.max(rect.height),
+            Some(ScreenEdge::Left) => left = left.max(rect.width),
+            Some(ScreenEdge::Right) => right = right.max(rect.width),
+            None => {}
+        }
+    }
+
+    Rect::new(
+        left as i32,
+        top as i32,
+        screen.width.saturating_sub(left + right),
+        screen.height.saturating_sub(top + bottom),
+    )
+}
@@ -3,16 +3,23 @@
 //! Representa uma janela gerenciada pelo compositor.
 
 use alloc::string::String;
+use alloc::vec::Vec;
 use gfx_types::color::Color;
 use gfx_types::geometry::{Point, Rect, Size};
 use gfx_types::window::{LayerType, WindowFlags, WindowState};
 use redpowder::ipc::SharedMemory;
 
+use super::damage::{area_checked, rect_contains};
+use crate::shm::as_u32_slice;
+
 // =============================================================================
 // WINDOW ID
 // =============================================================================
 
 /// ID único de janela.
+///
+/// No protocolo é um `u32`: índice do slot nos 16 bits baixos e geração
+/// nos 16 bits altos. A geração nunca é 0, então nenhum ID válido é 0.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct WindowId(pub u32);
 // TODO: Revisar no futuro
@@ -20,6 +27,24 @@ pub struct WindowId(pub u32);
 impl WindowId {
     pub const INVALID: Self = Self(0);
 
+    /// Monta o ID a partir de índice e geração.
+    #[inline]
+    pub const fn from_parts(index: u16, generation: u16) -> Self {
+        Self(((generation as u32) << 16) | index as u32)
+    }
+
+    /// Índice do slot.
+    #[inline]
+    pub const fn index(&self) -> u16 {
+        self.0 as u16
+    }
+
+    /// Geração do slot quando o ID foi alocado.
+    #[inline]
+    pub const fn generation(&self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
     #[inline]
     // TODO: Revisar no futuro
     #[allow(unused)]
@@ -28,6 +53,107 @@ impl WindowId {
     }
 }
 
+/// Alocador de IDs de janela com reuso de slots.
+///
+/// Cada slot liberado avança de geração, então um ID antigo de um slot
+/// reaproveitado não coincide com o da janela nova.
+#[derive(Default)]
+pub struct WindowIdAllocator {
+    /// Geração atual de cada slot.
+    generations: Vec<u16>,
+    /// Slots livres para reuso.
+    free: Vec<u16>,
+}
+
+impl WindowIdAllocator {
+    /// Cria alocador vazio.
+    pub const fn new() -> Self {
+        Self {
+            generations: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Aloca um ID. Retorna None se todos os slots estiverem em uso.
+    pub fn allocate(&mut self) -> Option<WindowId> {
+        if let Some(index) = self.free.pop() {
+            return Some(WindowId::from_parts(
+                index,
+                self.generations[index as usize],
+            ));
+        }
+
+        let index = u16::try_from(self.generations.len()).ok()?;
+        self.generations.push(1);
+        Some(WindowId::from_parts(index, 1))
+    }
+
+    /// Libera o slot do ID. IDs desconhecidos ou de geração antiga são ignorados.
+    pub fn release(&mut self, id: WindowId) {
+        let index = id.index();
+        let slot = match self.generations.get_mut(index as usize) {
+            Some(generation) if *generation == id.generation() => generation,
+            _ => return,
+        };
+
+        // Geração 0 é reservada (ID 0 = INVALID)
+        *slot = match slot.wrapping_add(1) {
+            0 => 1,
+            next => next,
+        };
+        self.free.push(index);
+    }
+}
+
+// =============================================================================
+// WINDOW TYPE
+// =============================================================================
+
+/// Papel da janela, que define comportamentos padrão.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WindowType {
+    /// Janela de aplicação comum.
+    #[default]
+    Normal,
+    /// Diálogo (centralizado sobre a janela com foco).
+    Dialog,
+    /// Dica flutuante.
+    Tooltip,
+    /// Menu popup.
+    Menu,
+    /// Dock / painel.
+    Dock,
+    /// Tela de abertura.
+    Splash,
+}
+
+impl WindowType {
+    /// Retorna se o tipo recebe decorações (título, foco).
+    #[inline]
+    pub fn has_decorations(self) -> bool {
+        matches!(self, Self::Normal | Self::Dialog)
+    }
+
+    /// Retorna se o tipo aparece na taskbar.
+    #[inline]
+    pub fn shows_in_taskbar(self) -> bool {
+        matches!(self, Self::Normal | Self::Dialog)
+    }
+}
+
+// =============================================================================
+// TELA CHEIA
+// =============================================================================
+
+/// Geometria e camada anteriores à tela cheia.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FullscreenRestore {
+    /// Retângulo antes da tela cheia.
+    pub rect: Rect,
+    /// Camada antes da promoção acima dos painéis.
+    pub layer: LayerType,
+}
+
 // =============================================================================
 // WINDOW
 // =============================================================================
@@ -39,9 +165,16 @@ pub struct Window {
     /// ID único.
     pub id: WindowId,
     /// Posição no desktop.
+    ///
+    /// Pode ser negativa ou passar da tela (janela arrastada para fora);
+    /// todo teste geométrico usa `clamp_rect`/`rect_contains`, que calculam
+    /// as bordas em `i64`.
     pub position: Point,
-    /// Tamanho da janela.
+    /// Tamanho da janela na tela.
     pub size: Size,
+    /// Tamanho do buffer na SHM (fixo desde a criação). `size` pode ser
+    /// maior (maximizada, tela cheia); o excedente não tem conteúdo.
+    pub buffer_size: Size,
     /// Memória compartilhada com o cliente.
     pub shm: SharedMemory,
     /// Flags de comportamento.
@@ -64,6 +197,27 @@ pub struct Window {
     pub opacity: u8,
     /// Cor de borda (se aplicável).
     pub border_color: Color,
+    /// Papel da janela.
+    pub window_type: WindowType,
+    /// Região que aceita input (coordenadas locais). Vazia = janela inteira.
+    pub input_region: Vec<Rect>,
+    /// Região declarada opaca pelo cliente (coordenadas locais), copiada
+    /// sem blending mesmo em janelas TRANSPARENT.
+    pub opaque_region: Option<Rect>,
+    /// Tag estável definida pelo cliente (vazia = sem tag).
+    pub tag: String,
+    /// Estado anterior, se a janela está em tela cheia.
+    pub fullscreen_restore: Option<FullscreenRestore>,
+    /// O usuário pode mover a janela (arraste, teclado, menu).
+    pub movable: bool,
+    /// O usuário pode redimensionar ou maximizar a janela.
+    pub resizable: bool,
+    /// Altura anterior, se a janela está enrolada (só a barra de título).
+    pub shade_restore_height: Option<u32>,
+    /// Fator de escala do buffer na tela (clientes sem suporte a HiDPI).
+    pub scale: u8,
+    /// Janela mãe de um diálogo: a janela fica sempre acima dela.
+    pub transient_for: Option<WindowId>,
 }
 
 impl Window {
@@ -73,6 +227,7 @@ impl Window {
             id: WindowId(id),
             position: Point::ZERO,
             size,
+            buffer_size: size,
             shm,
             flags: WindowFlags::NONE,
             state: WindowState::Normal,
@@ -84,6 +239,16 @@ impl Window {
             z_order: 0,
             opacity: 255,
             border_color: Color::TRANSPARENT,
+            window_type: WindowType::Normal,
+            input_region: Vec::new(),
+            opaque_region: None,
+            tag: String::new(),
+            fullscreen_restore: None,
+            movable: true,
+            resizable: true,
+            shade_restore_height: None,
+            scale: 1,
+            transient_for: None,
         }
     }
 
@@ -103,9 +268,11 @@ impl Window {
     }
 
     /// Retorna se a janela está visível.
+    ///
+    /// Janelas com opacidade 0 não são compostas nem recebem clicks.
     #[inline]
     pub fn is_visible(&self) -> bool {
-        self.state != WindowState::Minimized && self.has_content
+        self.state != WindowState::Minimized && self.has_content && self.opacity > 0
     }
 
     /// Retorna se a janela é transparente.
@@ -114,10 +281,66 @@ impl Window {
         self.flags.has(WindowFlags::TRANSPARENT) || self.opacity < 255
     }
 
-    /// Retorna se a janela tem decorações.
+    /// Retorna se a janela tem decorações (nunca em tela cheia).
     #[inline]
     pub fn has_decorations(&self) -> bool {
         !self.flags.has(WindowFlags::BORDERLESS)
+            && self.window_type.has_decorations()
+            && !self.is_fullscreen()
+    }
+
+    /// Retorna se a janela está em tela cheia.
+    #[inline]
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen_restore.is_some()
+    }
+
+    /// Retângulo de tela coberto pelo buffer (a partir da origem da janela),
+    /// limitado ao tamanho da janela (ex.: enrolada).
+    #[inline]
+    pub fn content_rect(&self) -> Rect {
+        let scaled = self.scaled_buffer_size();
+        Rect::new(
+            self.position.x,
+            self.position.y,
+            scaled.width.min(self.size.width),
+            scaled.height.min(self.size.height),
+        )
+    }
+
+    /// Tamanho do buffer na tela, já multiplicado por `scale`.
+    #[inline]
+    pub fn scaled_buffer_size(&self) -> Size {
+        let scale = self.scale.max(1) as u32;
+        Size::new(
+            self.buffer_size.width.saturating_mul(scale),
+            self.buffer_size.height.saturating_mul(scale),
+        )
+    }
+
+    /// Define o fator de escala; a janela passa a ocupar o buffer escalado.
+    pub fn set_scale(&mut self, scale: u8) {
+        let scale = scale.max(1);
+        if scale == self.scale {
+            return;
+        }
+        self.scale = scale;
+        if self.state == WindowState::Normal && !self.is_fullscreen() {
+            self.size = self.scaled_buffer_size();
+        }
+        self.dirty = true;
+    }
+
+    /// Retorna se a janela está enrolada.
+    #[inline]
+    pub fn is_shaded(&self) -> bool {
+        self.shade_restore_height.is_some()
+    }
+
+    /// Retorna se a janela aparece na taskbar.
+    #[inline]
+    pub fn shows_in_taskbar(&self) -> bool {
+        self.window_type.shows_in_taskbar()
     }
 
     /// Retorna se a janela tem sombra.
@@ -130,14 +353,20 @@ impl Window {
     // MODIFICAÇÕES
     // =========================================================================
 
-    // TODO: Revisar no futuro
-    #[allow(unused)]
     /// Altera layer da janela.
     pub fn set_layer(&mut self, layer: LayerType) {
         self.layer = layer;
         self.dirty = true;
     }
 
+    /// Define a opacidade global (0 = invisível, 255 = opaca).
+    pub fn set_opacity(&mut self, opacity: u8) {
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            self.dirty = true;
+        }
+    }
+
     /// Marca que a janela recebeu conteúdo.
     pub fn set_has_content(&mut self) {
         self.has_content = true;
@@ -200,12 +429,30 @@ impl Window {
         self.dirty = true;
     }
 
-    /// Maximiza a janela.
-    pub fn maximize(&mut self, screen_size: Size) {
+    /// Enrola a janela até `titlebar_height` ou desenrola. O buffer do
+    /// cliente não muda; só a parte composta e clicável.
+    pub fn set_shaded(&mut self, shaded: bool, titlebar_height: u32) {
+        if shaded == self.is_shaded() {
+            return;
+        }
+        if shaded {
+            self.shade_restore_height = Some(self.size.height);
+            self.size.height = self.size.height.min(titlebar_height);
+        } else if let Some(height) = self.shade_restore_height.take() {
+            self.size.height = height;
+        }
+        self.dirty = true;
+    }
+
+    /// Maximiza a janela para cobrir `area` (área de trabalho).
+    pub fn maximize(&mut self, area: Rect) {
+        if let Some(height) = self.shade_restore_height.take() {
+            self.size.height = height;
+        }
         if self.state != WindowState::Maximized {
             self.restore_rect = Some(self.rect());
-            self.position = Point::ZERO;
-            self.size = screen_size;
+            self.position = Point::new(area.x, area.y);
+            self.size = Size::new(area.width, area.height);
             self.state = WindowState::Maximized;
             self.dirty = true;
         }
@@ -215,26 +462,72 @@ impl Window {
     // ACESSO AOS PIXELS
     // =========================================================================
 
+    /// Verifica se a SHM ainda está mapeada e cobre o buffer da janela.
+    ///
+    /// Falha quando o mapeamento foi revogado (cliente morreu, kernel
+    /// recuperou a memória).
+    pub fn is_mapped(&self) -> bool {
+        area_checked(self.buffer_size).is_some_and(|count| as_u32_slice(&self.shm, count).is_some())
+    }
+
     /// Retorna pixels da janela como slice (acesso direto à SHM).
     ///
+    /// Retorna slice vazio se a SHM não estiver mapeada (ver [`Self::is_mapped`]).
+    ///
     /// # Safety
     /// O caller deve estar ciente de que o conteúdo pode ser alterado pelo cliente
     /// concorrentemente. No entanto, para composição, um blit sequencial é aceitável.
     pub fn pixels(&self) -> &[u32] {
-        let count = (self.size.width * self.size.height) as usize;
-        let src_ptr = self.shm.as_ptr() as *const u32;
-        unsafe { core::slice::from_raw_parts(src_ptr, count) }
+        area_checked(self.buffer_size)
+            .and_then(|count| as_u32_slice(&self.shm, count))
+            .unwrap_or(&[])
     }
 
     /// Verifica se um ponto está dentro da janela.
     #[inline]
     pub fn contains_point(&self, x: i32, y: i32) -> bool {
-        self.rect().contains_point(Point::new(x, y))
+        rect_contains(self.rect(), x, y)
     }
 
-    /// Converte coordenadas globais para locais da janela.
+    /// Converte coordenadas globais para locais da janela (pixels do
+    /// buffer, já divididos pela escala).
     #[inline]
     pub fn to_local(&self, x: i32, y: i32) -> Point {
-        Point::new(x - self.position.x, y - self.position.y)
+        let scale = self.scale.max(1) as i32;
+        Point::new(
+            (x - self.position.x).div_euclid(scale),
+            (y - self.position.y).div_euclid(scale),
+        )
+    }
+
+    /// Verifica se a janela recebe input no ponto (coordenadas globais).
+    ///
+    /// Pontos fora da região de input (se definida) e, em janelas com flag
+    /// TRANSPARENT, pixels totalmente transparentes deixam o click passar
+    /// para a janela de baixo.
+    pub fn accepts_input_at(&self, x: i32, y: i32) -> bool {
+        if !self.contains_point(x, y) {
+            return false;
+        }
+
+        let local = self.to_local(x, y);
+        if !self.input_region.is_empty()
+            && !self
+                .input_region
+                .iter()
+                .any(|&r| rect_contains(r, local.x, local.y))
+        {
+            return false;
+        }
+        if !self.flags.has(WindowFlags::TRANSPARENT) {
+            return true;
+        }
+
+        // Fora do buffer (maximizada/tela cheia) não há conteúdo
+        if local.x as u32 >= self.buffer_size.width {
+            return false;
+        }
+        let idx = local.y as usize * self.buffer_size.width as usize + local.x as usize;
+        self.pixels().get(idx).is_some_and(|pixel| pixel >> 24 != 0)
     }
 }
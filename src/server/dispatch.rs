@@ -2,21 +2,30 @@
 //!
 //! Dispatch de eventos para clientes.
 
+use gfx_types::geometry::Rect;
 use redpowder::event::{event_type, InputEvent};
 use redpowder::ipc::Port;
 use redpowder::window::{opcodes, WindowLifecycleEvent};
 
-use super::protocol::ClientPort;
+use super::protocol::{
+    ext_opcodes, remembered_state, truncate_title, CaptureFrameEvent, ClientPort, ConfigureEvent,
+    FrameDoneEvent, NotifyClickedEvent, PointerMotionEvent, PopupDismissedEvent,
+    SessionRestoreEvent, TouchEvent, WindowMemoryRecord, WireRect, MAX_TITLE_BYTES, SESSION_PORT,
+};
+use super::state::RememberedWindow;
 
 // =============================================================================
 // DISPATCH DE EVENTOS
 // =============================================================================
 
 /// Envia evento de teclado para uma janela.
+///
+/// `param2` carrega o caractere traduzido pelo keymap (0 se nenhum).
 pub fn dispatch_key_event(
     client_ports: &[ClientPort],
     window_id: u32,
     key_code: u32,
+    ch: Option<char>,
     pressed: bool,
 ) {
     let event = InputEvent {
@@ -27,7 +36,7 @@ pub fn dispatch_key_event(
             event_type::KEY_UP
         },
         param1: key_code,
-        param2: 0,
+        param2: ch.map_or(0, |c| c as u32),
     };
 
     send_event_to_window(client_ports, window_id, &event);
@@ -56,6 +65,76 @@ pub fn dispatch_mouse_event(
     send_event_to_window(client_ports, window_id, &event);
 }
 
+/// Envia movimento do ponteiro para a janela do grab implícito.
+pub fn dispatch_pointer_motion(
+    client_ports: &[ClientPort],
+    window_id: u32,
+    rel_x: i32,
+    rel_y: i32,
+    buttons: u32,
+) {
+    let event = PointerMotionEvent {
+        op: ext_opcodes::EVENT_POINTER_MOTION,
+        x: rel_x,
+        y: rel_y,
+        buttons,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<PointerMotionEvent>(),
+        )
+    };
+    send_bytes_to_window(client_ports, window_id, bytes);
+}
+
+/// Envia evento de toque para uma janela.
+pub fn dispatch_touch_event(
+    client_ports: &[ClientPort],
+    window_id: u32,
+    touch_id: u32,
+    phase: u32,
+    rel_x: i32,
+    rel_y: i32,
+) {
+    let event = TouchEvent {
+        op: ext_opcodes::EVENT_TOUCH,
+        phase,
+        touch_id,
+        x: rel_x,
+        y: rel_y,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<TouchEvent>(),
+        )
+    };
+    send_bytes_to_window(client_ports, window_id, bytes);
+}
+
+/// Envia a nova geometria da janela para o cliente.
+pub fn dispatch_configure_event(client_ports: &[ClientPort], window_id: u32, rect: Rect) {
+    let event = ConfigureEvent {
+        op: ext_opcodes::EVENT_CONFIGURE,
+        window_id,
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<ConfigureEvent>(),
+        )
+    };
+    send_bytes_to_window(client_ports, window_id, bytes);
+}
+
 /// Envia evento de lifecycle para a taskbar.
 pub fn send_lifecycle_event(
     taskbar_port: Option<&Port>,
@@ -64,10 +143,9 @@ pub fn send_lifecycle_event(
     title: &str,
 ) {
     if let Some(port) = taskbar_port {
-        let mut title_buf = [0u8; 64];
-        let bytes = title.as_bytes();
-        let len = bytes.len().min(64);
-        title_buf[..len].copy_from_slice(&bytes[..len]);
+        let mut title_buf = [0u8; MAX_TITLE_BYTES];
+        let bytes = truncate_title(title, MAX_TITLE_BYTES).as_bytes();
+        title_buf[..bytes.len()].copy_from_slice(bytes);
 
         let evt = WindowLifecycleEvent {
             op: opcodes::EVENT_WINDOW_LIFECYCLE,
@@ -86,6 +164,131 @@ pub fn send_lifecycle_event(
     }
 }
 
+/// Envia o frame callback para o cliente da janela.
+pub fn dispatch_frame_done(client_ports: &[ClientPort], window_id: u32, frame_time_ms: u64) {
+    let event = FrameDoneEvent {
+        op: ext_opcodes::EVENT_FRAME_DONE,
+        window_id,
+        frame_time_ms,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<FrameDoneEvent>(),
+        )
+    };
+    send_bytes_to_window(client_ports, window_id, bytes);
+}
+
+/// Avisa o cliente que o popup foi fechado por um click fora dele.
+pub fn dispatch_popup_dismissed(client_ports: &[ClientPort], window_id: u32) {
+    let event = PopupDismissedEvent {
+        op: ext_opcodes::EVENT_POPUP_DISMISSED,
+        window_id,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<PopupDismissedEvent>(),
+        )
+    };
+    send_bytes_to_window(client_ports, window_id, bytes);
+}
+
+/// Envia evento de click em notificação para a porta de origem.
+pub fn send_notify_clicked(port: &Port, notification_id: u32) {
+    let event = NotifyClickedEvent {
+        op: ext_opcodes::EVENT_NOTIFY_CLICKED,
+        notification_id,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<NotifyClickedEvent>(),
+        )
+    };
+    let _ = port.send(bytes, 0);
+}
+
+/// Avisa o gravador que um frame foi copiado para o buffer `index`.
+pub fn dispatch_capture_frame(port: &Port, index: u32, frame_time_ms: u64, dropped: u32) {
+    let event = CaptureFrameEvent {
+        op: ext_opcodes::EVENT_CAPTURE_FRAME,
+        index,
+        frame_time_ms,
+        dropped,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<CaptureFrameEvent>(),
+        )
+    };
+    let _ = port.send(bytes, 0);
+}
+
+/// Envia a geometria lembrada ao serviço de sessão.
+pub fn send_window_remembered(entry: &RememberedWindow) {
+    let mut tag = [0u8; 32];
+    let bytes = truncate_title(&entry.tag, tag.len()).as_bytes();
+    tag[..bytes.len()].copy_from_slice(bytes);
+
+    let state = if entry.maximized {
+        remembered_state::MAXIMIZED
+    } else if entry.minimized {
+        remembered_state::MINIMIZED
+    } else {
+        remembered_state::NORMAL
+    };
+
+    let record = WindowMemoryRecord {
+        op: ext_opcodes::EVENT_WINDOW_REMEMBERED,
+        tag,
+        rect: WireRect {
+            x: entry.rect.x,
+            y: entry.rect.y,
+            width: entry.rect.width,
+            height: entry.rect.height,
+        },
+        state,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &record as *const _ as *const u8,
+            core::mem::size_of::<WindowMemoryRecord>(),
+        )
+    };
+    send_to_session(bytes);
+}
+
+/// Pede ao serviço de sessão as geometrias lembradas (início do compositor).
+pub fn send_session_restore() {
+    let event = SessionRestoreEvent {
+        op: ext_opcodes::EVENT_SESSION_RESTORE,
+    };
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &event as *const _ as *const u8,
+            core::mem::size_of::<SessionRestoreEvent>(),
+        )
+    };
+    send_to_session(bytes);
+}
+
+/// Envia bytes ao serviço de sessão. Sem o serviço, a memória das janelas
+/// fica só no compositor.
+fn send_to_session(bytes: &[u8]) {
+    if let Ok(port) = Port::connect(SESSION_PORT) {
+        let _ = port.send(bytes, 0);
+    }
+}
+
 /// Envia evento para uma janela específica.
 fn send_event_to_window(client_ports: &[ClientPort], window_id: u32, event: &InputEvent) {
     let bytes = unsafe {
@@ -95,6 +298,11 @@ fn send_event_to_window(client_ports: &[ClientPort], window_id: u32, event: &Inp
         )
     };
 
+    send_bytes_to_window(client_ports, window_id, bytes);
+}
+
+/// Envia bytes brutos para a porta do cliente dono da janela.
+fn send_bytes_to_window(client_ports: &[ClientPort], window_id: u32, bytes: &[u8]) {
     if let Some(client) = client_ports.iter().find(|c| c.window_id == window_id) {
         let _ = client.port.send(bytes, 0);
     }
@@ -4,7 +4,7 @@
 
 use alloc::string::ToString;
 use alloc::vec::Vec;
-use gfx_types::geometry::Size;
+use gfx_types::geometry::{Point, Rect, Size};
 use gfx_types::window::{LayerType, WindowFlags};
 use redpowder::ipc::{Port, SharedMemory};
 use redpowder::syscall::SysResult;
@@ -13,145 +13,365 @@ use redpowder::window::{
     WindowCreatedResponse,
 };
 
+use crate::input::keymap::Keymap;
 use crate::render::RenderEngine;
+use crate::scene::placement::{keep_titlebar_reachable, panel_edge, place_relative};
+use crate::scene::{area_checked, buffer_bytes, WindowType};
+use crate::shm::as_u32_slice_mut;
+use crate::ui::decoration::{ButtonLayout, TITLEBAR_HEIGHT};
+use crate::ui::notification::Toast;
 
 use super::dispatch::send_lifecycle_event;
-use super::protocol::ClientPort;
+use super::protocol::{
+    button_layout_from_wire, c_str, compositor_option, decode, easing_from_wire, ext_opcodes,
+    gravity_from_wire, keymap_from_wire, layer_from_wire, remembered_state, truncate_title,
+    wallpaper_mode_from_wire, window_type_from_flags, ActivateWindowByTitleRequest,
+    CaptureReleaseRequest, CaptureStartedResponse, ClientPort, DamageRegionRequest,
+    GetHeartbeatRequest, GetPixelRequest, GetStatsRequest, HeartbeatResponse, NotifyRequest,
+    PixelResponse, QuotaExceededResponse, RefreshWindowRequest, RequestFrameRequest,
+    SetCompositorOptionRequest, SetConstraintsRequest, SetFullscreenRequest, SetInputRegionRequest,
+    SetKeymapRequest, SetOpacityRequest, SetOpaqueRegionRequest, SetPositionRelativeRequest,
+    SetPreeditRequest, SetTransientForRequest, SetWallpaperFromWindowRequest,
+    SetWallpaperModeRequest, SetWindowLayerRequest, SetWindowScaleRequest, SetWindowTagRequest,
+    ShadeWindowRequest, StartCaptureRequest, StatsResponse, WindowActivatedResponse,
+    WindowMemoryRecord, CONSTRAINT_NO_MOVE, CONSTRAINT_NO_RESIZE, DESKTOP_WIDGET_LAYER,
+    MAX_CAPTURE_BUFFERS, MAX_INPUT_REGION_RECTS, MAX_TITLE_BYTES, MAX_WINDOW_SCALE,
+};
+#[cfg(feature = "selftest")]
+use super::protocol::{
+    SelfTestRequest, SelfTestResponse, SELFTEST_BLIT, SELFTEST_FILL, SELFTEST_PRESENT,
+};
+use super::state::{ClientQuota, Heartbeat, PendingReply, RememberedWindow, WindowMemory};
+
+/// Tempo máximo (ms) aguardando a porta de resposta do cliente.
+const REPLY_CONNECT_TIMEOUT_MS: u64 = 2000;
 
 // =============================================================================
 // CREATE WINDOW
 // =============================================================================
 
 /// Handler para CREATE_WINDOW.
+///
+/// Retorna `None` se o pedido foi recusado (superfície maior que o limite
+/// relativo à tela, cota do cliente excedida ou IDs de janela esgotados).
 pub fn handle_create_window(
     render_engine: &mut RenderEngine,
     client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
     taskbar_port: Option<&Port>,
+    quota: &ClientQuota,
     data: &[u8],
-) -> SysResult<(u32, LayerType)> {
-    let req = unsafe { &*(data.as_ptr() as *const CreateWindowRequest) };
+    now_ms: u64,
+) -> SysResult<Option<(u32, LayerType)>> {
+    let req = match decode::<CreateWindowRequest>(data) {
+        Some(req) => req,
+        None => return Ok(None),
+    };
 
-    // 1. Criar memória compartilhada
-    let buffer_size = (req.width * req.height * 4) as usize;
-    let mut shm = SharedMemory::create(buffer_size)?;
+    // 1. Identificar cliente (porta de resposta) e verificar cota
+    let name_len = req
+        .reply_port
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(req.reply_port.len());
+    let port_name = core::str::from_utf8(&req.reply_port[..name_len]).ok();
 
-    // 2. Inicializar buffer com preto
-    let pixels = unsafe {
-        core::slice::from_raw_parts_mut(
-            shm.as_mut_ptr() as *mut u32,
-            (req.width * req.height) as usize,
-        )
+    // Recusar (sem reduzir) superfícies gigantes: o cliente só recebe
+    // `buffer_size`, então não saberia a largura reduzida.
+    let display = render_engine.size();
+    if !quota.surface_fits(req.width, req.height, display) {
+        redpowder::println!(
+            "[Firefly] Superfície {}x{} recusada para '{}' (tela {}x{}, máx {}x)",
+            req.width,
+            req.height,
+            port_name.unwrap_or(""),
+            display.width,
+            display.height,
+            quota.max_surface_scale
+        );
+        return Ok(None);
+    }
+    let size = Size::new(req.width, req.height);
+    let buffer_size = match buffer_bytes(size) {
+        Some(bytes) => bytes,
+        None => return Ok(None),
     };
-    pixels.fill(0xFF000000);
+
+    let client = port_name.unwrap_or("");
+    let (mut window_count, mut used_bytes) = ClientPort::usage(client_ports, client);
+    for reply in pending.iter().filter(|r| r.port_name == client) {
+        if let Some((_, shm_bytes)) = reply.client_window {
+            window_count += 1;
+            used_bytes += shm_bytes;
+        }
+    }
+    if !quota.allows(window_count, used_bytes, buffer_size) {
+        redpowder::println!(
+            "[Firefly] Cota excedida para '{}': {} janelas, {} bytes",
+            port_name.unwrap_or(""),
+            window_count,
+            used_bytes
+        );
+        if let Some(port_name) = port_name {
+            reject_over_quota(
+                client_ports,
+                pending,
+                port_name,
+                window_count,
+                used_bytes,
+                now_ms,
+            );
+        }
+        return Ok(None);
+    }
+
+    // 2. Criar memória compartilhada
+    let mut shm = match render_engine.allocate_window_buffer(buffer_size, client) {
+        Ok(shm) => shm,
+        Err(_) => {
+            redpowder::println!(
+                "[Firefly] Sem memória para janela {}x{} ({} bytes)",
+                req.width,
+                req.height,
+                buffer_size
+            );
+            return Ok(None);
+        }
+    };
+
+    // 3. Inicializar buffer com preto (inteiro: pode vir do pool com o
+    // conteúdo de outra janela do mesmo cliente)
+    let pixel_count = shm.size() / 4;
+    if let Some(pixels) = as_u32_slice_mut(&mut shm, pixel_count) {
+        pixels.fill(0xFF000000);
+    }
 
     let shm_id = shm.id();
-    let size = Size::new(req.width, req.height);
 
-    // 3. Determinar camada baseada em flags
+    // 4. Determinar camada baseada em flags e tipo
     let flags = WindowFlags::from_bits(req.flags);
-    let layer = determine_layer(&flags, req.y);
+    let window_type = window_type_from_flags(req.flags);
+    let requested = Rect::new(req.x as i32, req.y as i32, req.width, req.height);
+    let layer = determine_layer(&flags, window_type, requested, display);
 
-    // 4. Extrair título
-    let title_len = req
-        .title
-        .iter()
-        .position(|&c| c == 0)
-        .unwrap_or(req.title.len());
-    let title = core::str::from_utf8(&req.title[..title_len])
-        .unwrap_or("Untitled")
-        .to_string();
+    // 5. Extrair título
+    let title = truncate_title(c_str(&req.title), MAX_TITLE_BYTES).to_string();
 
-    // 5. Criar janela
-    let window_id = render_engine.create_window(size, shm, layer, title.clone());
+    // 6. Criar janela
+    let window_id = match render_engine.create_window(size, shm, layer, title.clone()) {
+        Some(id) => id,
+        None => {
+            redpowder::println!("[Firefly] Sem IDs de janela livres");
+            return Ok(None);
+        }
+    };
 
-    // 6. Posicionar
-    render_engine.move_window(window_id, req.x as i32, req.y as i32);
+    // 7. Posicionar (diálogos centralizados sobre a janela com foco)
+    let (x, y) = if window_type == WindowType::Dialog {
+        dialog_position(render_engine, size)
+    } else {
+        (req.x as i32, req.y as i32)
+    };
+    render_engine.move_window(window_id, x, y);
 
-    // 7. Aplicar flags
+    // 8. Aplicar flags e tipo
     if let Some(win) = render_engine.get_window_mut(window_id) {
         win.flags = flags;
+        win.window_type = window_type;
     }
 
-    // 8. Conectar porta de resposta
-    let name_len = req
-        .reply_port
-        .iter()
-        .position(|&c| c == 0)
-        .unwrap_or(req.reply_port.len());
-    if let Ok(port_name) = core::str::from_utf8(&req.reply_port[..name_len]) {
-        connect_and_respond(client_ports, port_name, window_id, shm_id.0, buffer_size);
+    // 9. Conectar porta de resposta
+    if let Some(port_name) = port_name {
+        connect_and_respond(
+            client_ports,
+            pending,
+            port_name,
+            window_id,
+            shm_id.0,
+            buffer_size,
+            now_ms,
+        );
     }
 
-    // 9. Notificar taskbar
-    send_lifecycle_event(taskbar_port, lifecycle_events::CREATED, window_id, &title);
+    // 10. Notificar taskbar
+    if window_type.shows_in_taskbar() {
+        send_lifecycle_event(taskbar_port, lifecycle_events::CREATED, window_id, &title);
+    }
 
     redpowder::println!(
-        "[Firefly] Janela {} criada: {}x{} layer={:?} '{}'",
+        "[Firefly] Janela {} criada: {}x{} layer={:?} tipo={:?} '{}'",
         window_id,
         req.width,
         req.height,
         layer,
+        window_type,
         title
     );
 
-    Ok((window_id, layer))
+    Ok(Some((window_id, layer)))
 }
 
-/// Determina a camada baseada nas flags.
-fn determine_layer(flags: &WindowFlags, y: u32) -> LayerType {
+/// Determina a camada baseada nas flags e no tipo da janela.
+///
+/// Janelas sem borda em `y == 0` ou ocupando uma borda inteira da tela viram
+/// painéis. Só as que ocupam a borda inteira (ver [`panel_edge`]) reservam
+/// faixa da área de trabalho.
+fn determine_layer(
+    flags: &WindowFlags,
+    window_type: WindowType,
+    rect: Rect,
+    screen: Size,
+) -> LayerType {
+    match window_type {
+        WindowType::Tooltip | WindowType::Menu => return LayerType::Overlay,
+        WindowType::Dock => return LayerType::Panel,
+        WindowType::Splash => return LayerType::Top,
+        WindowType::Normal | WindowType::Dialog => {}
+    }
+
     if flags.has(WindowFlags::OVERLAY) {
         LayerType::Overlay
     } else if flags.has(WindowFlags::BACKGROUND) {
         LayerType::Background
-    } else if flags.has(WindowFlags::BORDERLESS) && y == 0 {
+    } else if flags.has(WindowFlags::BORDERLESS)
+        && (rect.y == 0 || panel_edge(rect, screen).is_some())
+    {
         LayerType::Panel
     } else {
         LayerType::Normal
     }
 }
 
-/// Conecta à porta de resposta e envia response.
+/// Posição de um diálogo: centralizado sobre a janela com foco, ou na tela.
+fn dialog_position(render_engine: &RenderEngine, size: Size) -> (i32, i32) {
+    let parent = render_engine
+        .focused_window()
+        .and_then(|id| render_engine.get_window(id))
+        .map(|win| win.rect())
+        .unwrap_or_else(|| Rect::from_size(render_engine.size()));
+
+    (
+        parent.x + (parent.width as i32 - size.width as i32) / 2,
+        parent.y + (parent.height as i32 - size.height as i32) / 2,
+    )
+}
+
+/// Envia a resposta de criação e registra a porta do cliente.
 fn connect_and_respond(
     client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
     port_name: &str,
     window_id: u32,
     shm_handle: u64,
     buffer_size: usize,
+    now_ms: u64,
 ) {
-    for attempt in 0..10 {
-        match Port::connect(port_name) {
-            Ok(reply_port) => {
-                let response = WindowCreatedResponse {
-                    op: opcodes::WINDOW_CREATED,
-                    window_id,
-                    shm_handle,
-                    buffer_size: buffer_size as u64,
-                };
-
-                let resp_bytes = unsafe {
-                    core::slice::from_raw_parts(
-                        &response as *const _ as *const u8,
-                        core::mem::size_of::<WindowCreatedResponse>(),
-                    )
-                };
-                let _ = reply_port.send(resp_bytes, 0);
-
-                client_ports.push(ClientPort {
-                    window_id,
-                    port: reply_port,
-                });
-                break;
-            }
-            Err(_) if attempt < 9 => {
-                let _ = redpowder::time::sleep(10);
-            }
-            Err(e) => {
-                redpowder::println!("[Firefly] Falha ao conectar porta: {:?}", e);
+    let response = WindowCreatedResponse {
+        op: opcodes::WINDOW_CREATED,
+        window_id,
+        shm_handle,
+        buffer_size: buffer_size as u64,
+    };
+
+    deliver_reply(
+        client_ports,
+        pending,
+        PendingReply {
+            port_name: port_name.to_string(),
+            message: struct_bytes(&response),
+            client_window: Some((window_id, buffer_size)),
+            deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+        },
+    );
+}
+
+/// Avisa o cliente que CREATE_WINDOW foi recusado por cota.
+fn reject_over_quota(
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    port_name: &str,
+    window_count: usize,
+    used_bytes: usize,
+    now_ms: u64,
+) {
+    let response = QuotaExceededResponse {
+        op: ext_opcodes::QUOTA_EXCEEDED,
+        window_count: window_count as u32,
+        shm_bytes: used_bytes as u64,
+    };
+
+    deliver_reply(
+        client_ports,
+        pending,
+        PendingReply {
+            port_name: port_name.to_string(),
+            message: struct_bytes(&response),
+            client_window: None,
+            deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+        },
+    );
+}
+
+/// Tenta entregar a resposta agora; se a porta ainda não existe, enfileira.
+fn deliver_reply(
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    reply: PendingReply,
+) {
+    if let Some(reply) = try_deliver(client_ports, reply) {
+        pending.push(reply);
+    }
+}
+
+/// Tenta conectar (sem esperar) e enviar. Devolve a resposta se falhou.
+fn try_deliver(client_ports: &mut Vec<ClientPort>, reply: PendingReply) -> Option<PendingReply> {
+    let port = match Port::connect(&reply.port_name) {
+        Ok(port) => port,
+        Err(_) => return Some(reply),
+    };
+
+    let _ = port.send(&reply.message, 0);
+
+    if let Some((window_id, shm_bytes)) = reply.client_window {
+        client_ports.push(ClientPort {
+            window_id,
+            port,
+            client: reply.port_name,
+            shm_bytes,
+        });
+    }
+    None
+}
+
+/// Retenta as respostas pendentes (uma tentativa por iteração do loop).
+pub fn retry_pending_replies(
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    now_ms: u64,
+) {
+    if pending.is_empty() {
+        return;
+    }
+
+    for reply in core::mem::take(pending) {
+        if let Some(reply) = try_deliver(client_ports, reply) {
+            if now_ms >= reply.deadline_ms {
+                redpowder::println!("[Firefly] Timeout ao conectar porta '{}'", reply.port_name);
+            } else {
+                pending.push(reply);
             }
         }
     }
 }
 
+/// Copia uma estrutura `repr(C)` para bytes.
+fn struct_bytes<T>(value: &T) -> Vec<u8> {
+    unsafe {
+        core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>())
+    }
+    .to_vec()
+}
+
 // =============================================================================
 // DESTROY WINDOW
 // =============================================================================
@@ -165,9 +385,19 @@ pub fn handle_destroy_window(
 ) {
     redpowder::println!("[Firefly] Destruindo janela {}", window_id);
 
+    let owner = client_ports
+        .iter()
+        .find(|c| c.window_id == window_id)
+        .map(|c| c.client.clone())
+        .unwrap_or_default();
     client_ports.retain(|c| c.window_id != window_id);
-    send_lifecycle_event(taskbar_port, lifecycle_events::DESTROYED, window_id, "");
-    render_engine.destroy_window(window_id);
+    if render_engine
+        .get_window(window_id)
+        .is_some_and(|win| win.shows_in_taskbar())
+    {
+        send_lifecycle_event(taskbar_port, lifecycle_events::DESTROYED, window_id, "");
+    }
+    render_engine.destroy_window(window_id, &owner);
     render_engine.full_screen_damage();
 }
 
@@ -176,10 +406,15 @@ pub fn handle_destroy_window(
 // =============================================================================
 
 /// Handler para COMMIT_BUFFER.
+///
+/// Não precisa de fence contra a composição: o loop principal processa as
+/// mensagens e só depois compõe o frame, então um commit nunca é tratado
+/// enquanto a SHM da janela está sendo lida. O cliente, por sua vez, só deve
+/// voltar a escrever no buffer após o FRAME_DONE.
 pub fn handle_commit_buffer(render_engine: &mut RenderEngine, data: &[u8]) {
-    let req = unsafe { &*(data.as_ptr() as *const CommitBufferRequest) };
-    render_engine.mark_window_has_content(req.window_id);
-    render_engine.mark_damage(req.window_id);
+    if let Some(req) = decode::<CommitBufferRequest>(data) {
+        render_engine.commit_window(req.window_id);
+    }
 }
 
 // =============================================================================
@@ -194,8 +429,10 @@ pub fn handle_minimize_window(
 ) {
     if let Some(win) = render_engine.get_window_mut(window_id) {
         win.minimize();
-        let title = win.title.clone();
-        send_lifecycle_event(taskbar_port, lifecycle_events::MINIMIZED, window_id, &title);
+        if win.shows_in_taskbar() {
+            let title = win.title.clone();
+            send_lifecycle_event(taskbar_port, lifecycle_events::MINIMIZED, window_id, &title);
+        }
         render_engine.full_screen_damage();
         redpowder::println!("[Firefly] Janela {} minimizada", window_id);
     }
@@ -209,8 +446,10 @@ pub fn handle_restore_window(
 ) -> Option<u32> {
     if let Some(win) = render_engine.get_window_mut(window_id) {
         win.restore();
-        let title = win.title.clone();
-        send_lifecycle_event(taskbar_port, lifecycle_events::RESTORED, window_id, &title);
+        if win.shows_in_taskbar() {
+            let title = win.title.clone();
+            send_lifecycle_event(taskbar_port, lifecycle_events::RESTORED, window_id, &title);
+        }
         render_engine.full_screen_damage();
         render_engine.bring_to_front(window_id);
         redpowder::println!("[Firefly] Janela {} restaurada", window_id);
@@ -219,6 +458,748 @@ pub fn handle_restore_window(
     None
 }
 
+// =============================================================================
+// SET OPACITY
+// =============================================================================
+
+/// Handler para SET_OPACITY.
+pub fn handle_set_opacity(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<SetOpacityRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+    let opacity = req.opacity.min(255) as u8;
+    render_engine.set_window_opacity(req.window_id, opacity);
+}
+
+// =============================================================================
+// SET INPUT REGION
+// =============================================================================
+
+/// Handler para SET_INPUT_REGION.
+pub fn handle_set_input_region(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<SetInputRegionRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+    let count = (req.count as usize).min(MAX_INPUT_REGION_RECTS);
+
+    if let Some(win) = render_engine.get_window_mut(req.window_id) {
+        win.input_region = req.rects[..count]
+            .iter()
+            .map(|r| Rect::new(r.x, r.y, r.width, r.height))
+            .filter(|r| !r.is_empty())
+            .collect();
+    }
+}
+
+// =============================================================================
+// SET OPAQUE REGION
+// =============================================================================
+
+/// Handler para SET_OPAQUE_REGION.
+pub fn handle_set_opaque_region(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<SetOpaqueRegionRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let rect = Rect::new(req.rect.x, req.rect.y, req.rect.width, req.rect.height);
+    if let Some(win) = render_engine.get_window_mut(req.window_id) {
+        win.opaque_region = if rect.is_empty() { None } else { Some(rect) };
+    }
+}
+
+// =============================================================================
+// SET WINDOW TAG
+// =============================================================================
+
+/// Handler para SET_WINDOW_TAG.
+///
+/// Se a tag já foi vista, a janela volta à posição/estado lembrados. O
+/// tamanho não é restaurado: o buffer é do cliente e não há caminho de
+/// redimensionamento aqui. A posição é limitada como no arraste
+/// (`min_visible`), para a janela não voltar fora da tela se a resolução
+/// mudou. Retorna o ID da janela quando a geometria mudou e se ela deve ser
+/// minimizada.
+pub fn handle_set_window_tag(
+    render_engine: &mut RenderEngine,
+    memory: &WindowMemory,
+    min_visible: u32,
+    data: &[u8],
+) -> Option<(u32, bool)> {
+    let req = decode::<SetWindowTagRequest>(data)?;
+
+    let tag = c_str(&req.tag);
+
+    let area = render_engine.work_area();
+    let win = render_engine.get_window_mut(req.window_id)?;
+    win.tag = tag.to_string();
+
+    let saved = memory.lookup(tag)?;
+    win.position = keep_titlebar_reachable(
+        Point::new(saved.rect.x, saved.rect.y),
+        win.size,
+        TITLEBAR_HEIGHT,
+        area,
+        min_visible,
+    );
+    if saved.maximized {
+        win.maximize(area);
+    }
+    win.dirty = true;
+    redpowder::println!(
+        "[Firefly] Janela {} restaurada pela tag '{}'",
+        req.window_id,
+        tag
+    );
+
+    render_engine.full_screen_damage();
+    Some((req.window_id, saved.minimized))
+}
+
+/// Handler para LOAD_WINDOW_MEMORY: o serviço de sessão devolve uma
+/// geometria lembrada antes de um reinício.
+pub fn handle_load_window_memory(memory: &mut WindowMemory, data: &[u8]) {
+    let req = match decode::<WindowMemoryRecord>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let tag = c_str(&req.tag);
+    if tag.is_empty() {
+        return;
+    }
+    memory.remember(RememberedWindow {
+        tag: tag.to_string(),
+        rect: Rect::new(req.rect.x, req.rect.y, req.rect.width, req.rect.height),
+        maximized: req.state == remembered_state::MAXIMIZED,
+        minimized: req.state == remembered_state::MINIMIZED,
+    });
+}
+
+// =============================================================================
+// CAPTURA
+// =============================================================================
+
+/// Handler para START_CAPTURE.
+///
+/// Aloca o anel de buffers do tamanho da tela, responde com os handles e
+/// guarda a porta do gravador em `capture_port`. `buffer_count == 0` para
+/// a captura.
+pub fn handle_start_capture(
+    render_engine: &mut RenderEngine,
+    capture_port: &mut Option<Port>,
+    data: &[u8],
+) -> SysResult<()> {
+    let req = match decode::<StartCaptureRequest>(data) {
+        Some(req) => req,
+        None => return Ok(()),
+    };
+
+    render_engine.set_capture_sink(Vec::new());
+    *capture_port = None;
+    if req.buffer_count == 0 {
+        redpowder::println!("[Firefly] Captura encerrada");
+        return Ok(());
+    }
+    if render_engine.safe_mode_active() {
+        redpowder::println!("[Firefly] Captura recusada: modo seguro ativo");
+        return Ok(());
+    }
+
+    let port = match Port::connect(c_str(&req.reply_port)) {
+        Ok(port) => port,
+        Err(_) => {
+            redpowder::println!("[Firefly] Captura: porta do gravador indisponível");
+            return Ok(());
+        }
+    };
+
+    let size = render_engine.size();
+    let count = (req.buffer_count as usize).min(MAX_CAPTURE_BUFFERS);
+    let frame_bytes = buffer_bytes(size).unwrap_or(0);
+
+    let mut response = CaptureStartedResponse {
+        op: ext_opcodes::CAPTURE_STARTED,
+        width: size.width,
+        height: size.height,
+        buffer_count: count as u32,
+        shm_handles: [0; MAX_CAPTURE_BUFFERS],
+    };
+    let mut buffers = Vec::with_capacity(count);
+    for handle in response.shm_handles.iter_mut().take(count) {
+        let shm = SharedMemory::create(frame_bytes);
+        render_engine.record_allocation(shm.is_ok());
+        let shm = match shm {
+            Ok(shm) => shm,
+            Err(_) => {
+                redpowder::println!("[Firefly] Sem memória para os buffers de captura");
+                return Ok(());
+            }
+        };
+        *handle = shm.id().0;
+        buffers.push(shm);
+    }
+
+    let _ = port.send(&struct_bytes(&response), 0);
+    render_engine.set_capture_sink(buffers);
+    *capture_port = Some(port);
+
+    redpowder::println!(
+        "[Firefly] Captura iniciada: {} buffers de {}x{}",
+        count,
+        size.width,
+        size.height
+    );
+    Ok(())
+}
+
+/// Handler para CAPTURE_RELEASE.
+pub fn handle_capture_release(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<CaptureReleaseRequest>(data) {
+        render_engine.release_capture_buffer(req.index);
+    }
+}
+
+// =============================================================================
+// SET FULLSCREEN
+// =============================================================================
+
+/// Handler para SET_FULLSCREEN.
+///
+/// Retorna o ID da janela quando a geometria mudou.
+pub fn handle_set_fullscreen(render_engine: &mut RenderEngine, data: &[u8]) -> Option<u32> {
+    let req = decode::<SetFullscreenRequest>(data)?;
+
+    if render_engine.set_fullscreen(req.window_id, req.enabled != 0) {
+        Some(req.window_id)
+    } else {
+        None
+    }
+}
+
+// =============================================================================
+// SET CONSTRAINTS
+// =============================================================================
+
+/// Handler para SET_CONSTRAINTS.
+pub fn handle_set_constraints(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<SetConstraintsRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    if let Some(win) = render_engine.get_window_mut(req.window_id) {
+        win.movable = req.flags & CONSTRAINT_NO_MOVE == 0;
+        win.resizable = req.flags & CONSTRAINT_NO_RESIZE == 0;
+    }
+}
+
+// =============================================================================
+// SET WINDOW SCALE
+// =============================================================================
+
+/// Handler para SET_WINDOW_SCALE.
+pub fn handle_set_window_scale(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<SetWindowScaleRequest>(data) {
+        let scale = req.scale.clamp(1, MAX_WINDOW_SCALE) as u8;
+        render_engine.set_window_scale(req.window_id, scale);
+    }
+}
+
+// =============================================================================
+// SET TRANSIENT FOR
+// =============================================================================
+
+/// Handler para SET_TRANSIENT_FOR.
+pub fn handle_set_transient_for(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<SetTransientForRequest>(data) {
+        let parent = (req.parent_id != 0).then_some(req.parent_id);
+        if !render_engine.set_transient_for(req.window_id, parent) {
+            redpowder::println!(
+                "[Firefly] Janela {} não pode ser transient de {}",
+                req.window_id,
+                req.parent_id
+            );
+        }
+    }
+}
+
+// =============================================================================
+// DAMAGE REGION
+// =============================================================================
+
+/// Handler para DAMAGE_REGION (aceito de qualquer cliente).
+pub fn handle_damage_region(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<DamageRegionRequest>(data) {
+        let rect = Rect::new(req.x, req.y, req.width, req.height);
+        if render_engine.damage_region(rect).is_none() {
+            redpowder::println!(
+                "[Firefly] DAMAGE_REGION fora da tela: {}x{} em ({}, {})",
+                req.width,
+                req.height,
+                req.x,
+                req.y
+            );
+        }
+    }
+}
+
+// =============================================================================
+// SHADE WINDOW
+// =============================================================================
+
+/// Handler para SHADE_WINDOW.
+pub fn handle_shade_window(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<ShadeWindowRequest>(data) {
+        render_engine.set_shaded(req.window_id, req.shaded != 0);
+    }
+}
+
+// =============================================================================
+// SET PREEDIT
+// =============================================================================
+
+/// Handler para SET_PREEDIT.
+pub fn handle_set_preedit(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<SetPreeditRequest>(data) {
+        render_engine.set_preedit(
+            req.window_id,
+            c_str(&req.text).to_string(),
+            Point::new(req.cursor_x, req.cursor_y),
+        );
+    }
+}
+
+// =============================================================================
+// SET WINDOW LAYER
+// =============================================================================
+
+/// Handler para SET_WINDOW_LAYER.
+///
+/// Camadas privilegiadas (Lock e Cursor) são reservadas ao compositor.
+pub fn handle_set_window_layer(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<SetWindowLayerRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    if req.layer == DESKTOP_WIDGET_LAYER {
+        render_engine.set_desktop_widget(req.window_id);
+        redpowder::println!(
+            "[Firefly] Janela {} movida para widgets de desktop",
+            req.window_id
+        );
+        return;
+    }
+
+    let layer = match layer_from_wire(req.layer) {
+        Some(LayerType::Lock) | Some(LayerType::Cursor) | None => {
+            redpowder::println!(
+                "[Firefly] Camada {} rejeitada para janela {}",
+                req.layer,
+                req.window_id
+            );
+            return;
+        }
+        Some(layer) => layer,
+    };
+
+    render_engine.set_window_layer(req.window_id, layer);
+    redpowder::println!(
+        "[Firefly] Janela {} movida para layer={:?}",
+        req.window_id,
+        layer
+    );
+}
+
+// =============================================================================
+// SET WALLPAPER MODE
+// =============================================================================
+
+/// Handler para SET_WALLPAPER_MODE.
+pub fn handle_set_wallpaper_mode(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<SetWallpaperModeRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    match wallpaper_mode_from_wire(req.mode) {
+        Some(mode) => {
+            render_engine.set_wallpaper_mode(mode);
+            redpowder::println!("[Firefly] Modo do wallpaper: {:?}", mode);
+        }
+        None => {
+            redpowder::println!("[Firefly] Modo de wallpaper inválido: {}", req.mode);
+        }
+    }
+}
+
+/// Handler para SET_WALLPAPER_FROM_WINDOW.
+pub fn handle_set_wallpaper_from_window(render_engine: &mut RenderEngine, data: &[u8]) {
+    if let Some(req) = decode::<SetWallpaperFromWindowRequest>(data) {
+        if render_engine.set_wallpaper_from_window(req.window_id) {
+            redpowder::println!("[Firefly] Wallpaper definido pela janela {}", req.window_id);
+        } else {
+            redpowder::println!(
+                "[Firefly] Janela {} sem conteúdo válido para wallpaper",
+                req.window_id
+            );
+        }
+    }
+}
+
+// =============================================================================
+// SET POSITION RELATIVE
+// =============================================================================
+
+/// Handler para SET_POSITION_RELATIVE. Retorna o ID da janela movida.
+pub fn handle_set_position_relative(render_engine: &mut RenderEngine, data: &[u8]) -> Option<u32> {
+    let req = decode::<SetPositionRelativeRequest>(data)?;
+
+    if req.window_id == req.parent_id {
+        redpowder::println!(
+            "[Firefly] Janela {} não pode ser posicionada relativa a si mesma",
+            req.window_id
+        );
+        return None;
+    }
+
+    let gravity = match gravity_from_wire(req.gravity) {
+        Some(g) => g,
+        None => {
+            redpowder::println!("[Firefly] Gravidade inválida: {}", req.gravity);
+            return None;
+        }
+    };
+
+    let parent = render_engine.get_window(req.parent_id)?.rect();
+    let size = render_engine.get_window(req.window_id)?.size;
+    let screen = Rect::from_size(render_engine.size());
+
+    let anchor = Point::new(req.anchor_x, req.anchor_y);
+    let pos = place_relative(parent, anchor, size, gravity, screen);
+    render_engine.move_window(req.window_id, pos.x, pos.y);
+
+    Some(req.window_id)
+}
+
+// =============================================================================
+// REQUEST FRAME
+// =============================================================================
+
+/// Handler para REQUEST_FRAME.
+pub fn handle_request_frame(render_engine: &mut RenderEngine, data: &[u8]) {
+    let req = match decode::<RequestFrameRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+    render_engine.request_frame(req.window_id);
+}
+
+// =============================================================================
+// REFRESH WINDOW
+// =============================================================================
+
+/// Handler para REFRESH_WINDOW. Retorna o ID da janela se existir.
+pub fn handle_refresh_window(render_engine: &mut RenderEngine, data: &[u8]) -> Option<u32> {
+    let req = decode::<RefreshWindowRequest>(data)?;
+
+    if render_engine.refresh_window(req.window_id) {
+        Some(req.window_id)
+    } else {
+        None
+    }
+}
+
+// =============================================================================
+// GET PIXEL
+// =============================================================================
+
+/// Handler para GET_PIXEL: responde com a cor composta no ponto.
+pub fn handle_get_pixel(
+    render_engine: &RenderEngine,
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    data: &[u8],
+    now_ms: u64,
+) {
+    let req = match decode::<GetPixelRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let port_name = c_str(&req.reply_port);
+    if port_name.is_empty() {
+        return;
+    }
+
+    let color = render_engine.backbuffer_pixel(req.x, req.y);
+    let response = PixelResponse {
+        op: ext_opcodes::PIXEL,
+        x: req.x,
+        y: req.y,
+        color: color.unwrap_or(0),
+        valid: color.is_some() as u32,
+    };
+
+    deliver_reply(
+        client_ports,
+        pending,
+        PendingReply {
+            port_name: port_name.to_string(),
+            message: struct_bytes(&response),
+            client_window: None,
+            deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+        },
+    );
+}
+
+// =============================================================================
+// ACTIVATE WINDOW BY TITLE
+// =============================================================================
+
+/// Handler para ACTIVATE_WINDOW_BY_TITLE.
+///
+/// Responde com o ID encontrado (0 se nenhum) e o retorna para o servidor
+/// restaurar, trazer à frente e focar a janela.
+pub fn handle_activate_window_by_title(
+    render_engine: &RenderEngine,
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    data: &[u8],
+    now_ms: u64,
+) -> Option<u32> {
+    let req = decode::<ActivateWindowByTitleRequest>(data)?;
+
+    let title = truncate_title(c_str(&req.title), MAX_TITLE_BYTES);
+    let found = render_engine.find_window_by_title(title);
+
+    let port_name = c_str(&req.reply_port);
+    if !port_name.is_empty() {
+        let response = WindowActivatedResponse {
+            op: ext_opcodes::WINDOW_ACTIVATED,
+            window_id: found.unwrap_or(0),
+        };
+        deliver_reply(
+            client_ports,
+            pending,
+            PendingReply {
+                port_name: port_name.to_string(),
+                message: struct_bytes(&response),
+                client_window: None,
+                deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+            },
+        );
+    }
+
+    found
+}
+
+// =============================================================================
+// HEARTBEAT
+// =============================================================================
+
+/// Handler para GET_HEARTBEAT: responde com o progresso do loop principal.
+pub fn handle_get_heartbeat(
+    render_engine: &RenderEngine,
+    heartbeat: &Heartbeat,
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    data: &[u8],
+    now_ms: u64,
+) {
+    let req = match decode::<GetHeartbeatRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let port_name = c_str(&req.reply_port);
+    if port_name.is_empty() {
+        return;
+    }
+
+    let (_, window_count) = render_engine.stats();
+    let response = HeartbeatResponse {
+        op: ext_opcodes::HEARTBEAT,
+        window_count: window_count as u32,
+        frames: heartbeat.frames,
+    };
+
+    deliver_reply(
+        client_ports,
+        pending,
+        PendingReply {
+            port_name: port_name.to_string(),
+            message: struct_bytes(&response),
+            client_window: None,
+            deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+        },
+    );
+}
+
+// =============================================================================
+// STATS
+// =============================================================================
+
+/// Handler para GET_STATS: responde com as estatísticas do último frame.
+pub fn handle_get_stats(
+    render_engine: &RenderEngine,
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    data: &[u8],
+    now_ms: u64,
+) {
+    let req = match decode::<GetStatsRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let port_name = c_str(&req.reply_port);
+    if port_name.is_empty() {
+        return;
+    }
+
+    let (_, window_count) = render_engine.stats();
+    let frame = render_engine.frame_stats();
+    let response = StatsResponse {
+        op: ext_opcodes::STATS,
+        window_count: window_count as u32,
+        pixels_written: frame.pixels_written,
+        damaged_pixels: frame.damaged_pixels,
+        screen_pixels: area_checked(render_engine.size()).unwrap_or(0) as u64,
+    };
+
+    deliver_reply(
+        client_ports,
+        pending,
+        PendingReply {
+            port_name: port_name.to_string(),
+            message: struct_bytes(&response),
+            client_window: None,
+            deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+        },
+    );
+}
+
+// =============================================================================
+// SELFTEST
+// =============================================================================
+
+/// Handler para SELFTEST: executa o self-test e responde com o resultado.
+#[cfg(feature = "selftest")]
+pub fn handle_self_test(
+    render_engine: &mut RenderEngine,
+    client_ports: &mut Vec<ClientPort>,
+    pending: &mut Vec<PendingReply>,
+    data: &[u8],
+    now_ms: u64,
+) {
+    let req = match decode::<SelfTestRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let report = render_engine.run_self_test();
+    let checks = [
+        (SELFTEST_FILL, report.fill),
+        (SELFTEST_BLIT, report.blit),
+        (SELFTEST_PRESENT, report.present),
+    ];
+    let passed = checks
+        .iter()
+        .filter(|(_, ok)| *ok)
+        .fold(0, |mask, (flag, _)| mask | flag);
+    let failed = (SELFTEST_FILL | SELFTEST_BLIT | SELFTEST_PRESENT) & !passed;
+
+    let port_name = c_str(&req.reply_port);
+    if port_name.is_empty() {
+        return;
+    }
+
+    let response = SelfTestResponse {
+        op: ext_opcodes::SELFTEST_RESULT,
+        passed,
+        failed,
+    };
+
+    deliver_reply(
+        client_ports,
+        pending,
+        PendingReply {
+            port_name: port_name.to_string(),
+            message: struct_bytes(&response),
+            client_window: None,
+            deadline_ms: now_ms + REPLY_CONNECT_TIMEOUT_MS,
+        },
+    );
+}
+
+// =============================================================================
+// SET KEYMAP
+// =============================================================================
+
+/// Handler para SET_KEYMAP. Retorna o novo layout se válido.
+pub fn handle_set_keymap(data: &[u8]) -> Option<Keymap> {
+    let req = decode::<SetKeymapRequest>(data)?;
+
+    let keymap = keymap_from_wire(req.keymap);
+    match keymap {
+        Some(keymap) => redpowder::println!("[Firefly] Keymap: {:?}", keymap),
+        None => redpowder::println!("[Firefly] Keymap inválido: {}", req.keymap),
+    }
+    keymap
+}
+
+// =============================================================================
+// SET COMPOSITOR OPTION
+// =============================================================================
+
+/// Handler para SET_COMPOSITOR_OPTION.
+pub fn handle_set_compositor_option(
+    render_engine: &mut RenderEngine,
+    touch_emulates_mouse: &mut bool,
+    button_layout: &mut ButtonLayout,
+    data: &[u8],
+) {
+    let req = match decode::<SetCompositorOptionRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let enabled = req.value != 0;
+    match req.option {
+        compositor_option::DIM_INACTIVE => render_engine.set_dim_inactive(enabled),
+        compositor_option::FADE_IN => render_engine.set_fade_in(enabled),
+        compositor_option::FADE_EASING => match easing_from_wire(req.value) {
+            Some(easing) => render_engine.set_fade_easing(easing),
+            None => {
+                redpowder::println!("[Firefly] Curva inválida: {}", req.value);
+                return;
+            }
+        },
+        compositor_option::SKIP_IDENTICAL_FRAMES => {
+            render_engine.set_skip_identical_frames(enabled)
+        }
+        compositor_option::SAFE_MODE => render_engine.set_safe_mode(enabled),
+        compositor_option::SAFE_MODE_THRESHOLD => render_engine.set_safe_mode_threshold(req.value),
+        compositor_option::TOUCH_EMULATES_MOUSE => *touch_emulates_mouse = enabled,
+        compositor_option::BUTTON_LAYOUT => match button_layout_from_wire(req.value) {
+            Some(layout) => *button_layout = layout,
+            None => {
+                redpowder::println!("[Firefly] Disposição de botões inválida: {:#x}", req.value);
+                return;
+            }
+        },
+        _ => {
+            redpowder::println!("[Firefly] Opção inválida: {}", req.option);
+            return;
+        }
+    }
+    redpowder::println!("[Firefly] Opção {} = {}", req.option, req.value);
+}
+
 // =============================================================================
 // REGISTER TASKBAR
 // =============================================================================
@@ -242,3 +1223,40 @@ pub fn handle_register_taskbar(req: &RegisterTaskbarRequest) -> Option<Port> {
     }
     None
 }
+
+// =============================================================================
+// NOTIFY
+// =============================================================================
+
+/// Handler para NOTIFY.
+pub fn handle_notify(render_engine: &mut RenderEngine, data: &[u8], now_ms: u64) {
+    let req = match decode::<NotifyRequest>(data) {
+        Some(req) => req,
+        None => return,
+    };
+
+    let title = c_str(&req.title).to_string();
+    let body = c_str(&req.body).to_string();
+
+    let port_name = c_str(&req.reply_port);
+    let port = if port_name.is_empty() {
+        None
+    } else {
+        Port::connect(port_name).ok()
+    };
+
+    redpowder::println!(
+        "[Firefly] Notificação {}: '{}' ({} ms)",
+        req.notification_id,
+        title,
+        req.duration_ms
+    );
+
+    render_engine.notify(Toast {
+        id: req.notification_id,
+        title,
+        body,
+        expires_at: now_ms + req.duration_ms as u64,
+        port,
+    });
+}
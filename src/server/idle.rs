@@ -0,0 +1,73 @@
+//! # Idle
+//!
+//! Escurecimento e desligamento da tela após inatividade de input.
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Tempo sem input até começar a escurecer (ms).
+const DEFAULT_DIM_AFTER_MS: u64 = 5 * 60 * 1000;
+
+/// Tempo sem input até apagar a tela (ms).
+const DEFAULT_BLANK_AFTER_MS: u64 = 10 * 60 * 1000;
+
+/// Duração da transição até o escurecimento máximo (ms).
+const DIM_FADE_MS: u64 = 2000;
+
+/// Intensidade máxima do escurecimento (alpha do overlay preto).
+const DIM_MAX: u8 = 0xA0;
+
+// =============================================================================
+// IDLE MANAGER
+// =============================================================================
+
+/// Acompanha o tempo desde o último input e calcula o nível de escurecimento.
+pub struct IdleManager {
+    /// Instante do último input (ms).
+    last_input_ms: u64,
+    /// Inatividade até começar a escurecer (ms).
+    dim_after_ms: u64,
+    /// Inatividade até apagar a tela (ms).
+    blank_after_ms: u64,
+}
+
+impl IdleManager {
+    pub fn new() -> Self {
+        Self {
+            last_input_ms: 0,
+            dim_after_ms: DEFAULT_DIM_AFTER_MS,
+            blank_after_ms: DEFAULT_BLANK_AFTER_MS,
+        }
+    }
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Configura os tempos de inatividade (ms).
+    ///
+    /// `blank_after_ms` menor que `dim_after_ms` apaga a tela sem escurecer antes.
+    pub fn set_timeouts(&mut self, dim_after_ms: u64, blank_after_ms: u64) {
+        self.dim_after_ms = dim_after_ms;
+        self.blank_after_ms = blank_after_ms;
+    }
+
+    /// Registra um evento de input (cancela escurecimento/tela apagada).
+    pub fn input(&mut self, now_ms: u64) {
+        self.last_input_ms = now_ms;
+    }
+
+    /// Alpha do overlay preto: 0 = normal, `u8::MAX` = tela apagada.
+    pub fn dim_level(&self, now_ms: u64) -> u8 {
+        let idle_ms = now_ms.saturating_sub(self.last_input_ms);
+
+        if idle_ms >= self.blank_after_ms {
+            return u8::MAX;
+        }
+        if idle_ms < self.dim_after_ms {
+            return 0;
+        }
+
+        let elapsed = (idle_ms - self.dim_after_ms).min(DIM_FADE_MS);
+        (DIM_MAX as u64 * elapsed / DIM_FADE_MS) as u8
+    }
+}
@@ -6,12 +6,16 @@
 //!
 //! - **server**: Servidor principal e loop de eventos
 //! - **handlers**: Handlers de mensagens IPC
+//! - **registry**: Registro de opcodes (tabela de despacho)
 //! - **dispatch**: Dispatch de eventos para clientes
 //! - **state**: Estado do servidor (foco, drag, etc)
+//! - **idle**: Escurecimento/desligamento da tela por inatividade
 
 mod dispatch;
 mod handlers;
+mod idle;
 mod protocol;
+mod registry;
 mod server;
 mod state;
 
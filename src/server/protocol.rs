@@ -2,6 +2,180 @@
 //!
 //! Estruturas de protocolo IPC do servidor.
 
+use alloc::string::String;
+use alloc::vec::Vec;
+use gfx_types::window::LayerType;
+use redpowder::window::{
+    opcodes, CommitBufferRequest, CreateWindowRequest, DestroyWindowRequest,
+    RegisterTaskbarRequest, WindowOpRequest,
+};
+
+use crate::input::keymap::Keymap;
+use crate::render::easing::Easing;
+use crate::render::wallpaper::WallpaperMode;
+use crate::scene::placement::Gravity;
+use crate::scene::WindowType;
+use crate::ui::decoration::{ButtonLayout, ButtonSide, TitlebarButton};
+
+// =============================================================================
+// OPCODES DE EXTENSÃO
+// =============================================================================
+
+/// Opcodes próprios do Firefly, ainda não presentes em `redpowder::window::opcodes`.
+pub mod ext_opcodes {
+    /// Evento: geometria da janela mudou.
+    pub const EVENT_CONFIGURE: u32 = 0x100;
+    /// Request: exibir notificação.
+    pub const NOTIFY: u32 = 0x101;
+    /// Evento: notificação clicada.
+    pub const EVENT_NOTIFY_CLICKED: u32 = 0x102;
+    /// Request: alterar opacidade da janela.
+    pub const SET_OPACITY: u32 = 0x103;
+    /// Request: mover janela para outra camada.
+    pub const SET_WINDOW_LAYER: u32 = 0x104;
+    /// Request: alterar modo do wallpaper.
+    pub const SET_WALLPAPER_MODE: u32 = 0x105;
+    /// Request: alterar layout de teclado.
+    pub const SET_KEYMAP: u32 = 0x106;
+    /// Request: redesenhar a janela inteira.
+    pub const REFRESH_WINDOW: u32 = 0x107;
+    /// Resposta: CREATE_WINDOW recusado por exceder a cota do cliente.
+    pub const QUOTA_EXCEEDED: u32 = 0x108;
+    /// Request: posicionar janela relativa a outra (popups/menus).
+    pub const SET_POSITION_RELATIVE: u32 = 0x109;
+    /// Request: ler a cor composta em um ponto da tela.
+    pub const GET_PIXEL: u32 = 0x10A;
+    /// Resposta: cor composta (ver [`super::PixelResponse`]).
+    pub const PIXEL: u32 = 0x10B;
+    /// Request (serviço de input): atualização de toque.
+    pub const TOUCH_UPDATE: u32 = 0x10C;
+    /// Evento: toque em uma janela.
+    pub const EVENT_TOUCH: u32 = 0x10D;
+    /// Request: avisar quando a janela for composta no próximo frame.
+    pub const REQUEST_FRAME: u32 = 0x10E;
+    /// Evento: frame com a janela foi composto.
+    pub const EVENT_FRAME_DONE: u32 = 0x10F;
+    /// Request (supervisor): consultar o heartbeat do loop principal.
+    pub const GET_HEARTBEAT: u32 = 0x110;
+    /// Resposta: heartbeat (ver [`super::HeartbeatResponse`]).
+    pub const HEARTBEAT: u32 = 0x111;
+    /// Request: definir a região da janela que aceita input.
+    pub const SET_INPUT_REGION: u32 = 0x112;
+    /// Request: declarar a região opaca da janela.
+    pub const SET_OPAQUE_REGION: u32 = 0x113;
+    /// Request: identificar a janela para lembrar sua posição entre execuções.
+    pub const SET_WINDOW_TAG: u32 = 0x114;
+    /// Request: entrar/sair da tela cheia.
+    pub const SET_FULLSCREEN: u32 = 0x115;
+    /// Request (gravador): iniciar/parar a captura de frames.
+    pub const START_CAPTURE: u32 = 0x116;
+    /// Resposta: buffers de captura (ver [`super::CaptureStartedResponse`]).
+    pub const CAPTURE_STARTED: u32 = 0x117;
+    /// Evento: frame copiado para um buffer de captura.
+    pub const EVENT_CAPTURE_FRAME: u32 = 0x118;
+    /// Request (gravador): buffer de captura lido, pode ser reutilizado.
+    pub const CAPTURE_RELEASE: u32 = 0x119;
+    /// Request: impedir que o usuário mova ou redimensione a janela.
+    pub const SET_CONSTRAINTS: u32 = 0x11A;
+    /// Request: exibir o texto de composição do IME (preedit).
+    pub const SET_PREEDIT: u32 = 0x11B;
+    /// Request: enrolar/desenrolar a janela (só a barra de título).
+    pub const SHADE_WINDOW: u32 = 0x11C;
+    /// Request (ferramentas): recompor uma área da tela no próximo frame.
+    pub const DAMAGE_REGION: u32 = 0x11D;
+    /// Request: ampliar a janela na tela (cliente sem suporte a HiDPI).
+    pub const SET_WINDOW_SCALE: u32 = 0x11E;
+    /// Request (automação): focar a janela com um título.
+    pub const ACTIVATE_WINDOW_BY_TITLE: u32 = 0x11F;
+    /// Resposta: janela ativada (ver [`super::WindowActivatedResponse`]).
+    pub const WINDOW_ACTIVATED: u32 = 0x120;
+    /// Request: declarar a janela mãe de um diálogo.
+    pub const SET_TRANSIENT_FOR: u32 = 0x121;
+    /// Evento: popup fechado por um click fora dele.
+    pub const EVENT_POPUP_DISMISSED: u32 = 0x122;
+    /// Request (diagnóstico): consultar estatísticas de renderização.
+    pub const GET_STATS: u32 = 0x123;
+    /// Resposta: estatísticas (ver [`super::StatsResponse`]).
+    pub const STATS: u32 = 0x124;
+    /// Request (diagnóstico, feature `selftest`): desenhar padrões de teste
+    /// e apresentar.
+    #[cfg(feature = "selftest")]
+    pub const SELFTEST: u32 = 0x125;
+    /// Resposta: resultado do self-test (ver [`super::SelfTestResponse`]).
+    #[cfg(feature = "selftest")]
+    pub const SELFTEST_RESULT: u32 = 0x126;
+    /// Request: usar o conteúdo de uma janela como wallpaper.
+    pub const SET_WALLPAPER_FROM_WINDOW: u32 = 0x127;
+    /// Evento: movimento do ponteiro durante o grab implícito.
+    pub const EVENT_POINTER_MOTION: u32 = 0x128;
+    /// Evento (serviço de sessão): geometria de uma janela com tag destruída.
+    pub const EVENT_WINDOW_REMEMBERED: u32 = 0x129;
+    /// Request (serviço de sessão): geometria lembrada a restaurar.
+    pub const LOAD_WINDOW_MEMORY: u32 = 0x12A;
+    /// Evento (serviço de sessão): o compositor iniciou e quer as geometrias
+    /// lembradas de volta (via `LOAD_WINDOW_MEMORY`).
+    pub const EVENT_SESSION_RESTORE: u32 = 0x12B;
+    /// Request: mudar uma opção do compositor (ver [`super::compositor_option`]).
+    pub const SET_COMPOSITOR_OPTION: u32 = 0x12C;
+}
+
+/// Opções em `SetCompositorOptionRequest::option`. Valores booleanos usam
+/// 0 = desligado, qualquer outro = ligado.
+pub mod compositor_option {
+    /// Escurecer janelas normais sem foco.
+    pub const DIM_INACTIVE: u32 = 0;
+    /// Fade-in de janelas novas.
+    pub const FADE_IN: u32 = 1;
+    /// Curva do fade-in (ver [`super::easing_from_wire`]).
+    pub const FADE_EASING: u32 = 2;
+    /// Pular o present de frames idênticos ao anterior.
+    pub const SKIP_IDENTICAL_FRAMES: u32 = 3;
+    /// Modo seguro (efeitos desligados).
+    pub const SAFE_MODE: u32 = 4;
+    /// Falhas seguidas de alocação que ativam o modo seguro.
+    pub const SAFE_MODE_THRESHOLD: u32 = 5;
+    /// Toque primário também gera eventos de mouse (apps sem suporte a
+    /// toque). Desligado por padrão.
+    pub const TOUCH_EMULATES_MOUSE: u32 = 6;
+    /// Disposição dos botões da barra de título (ver
+    /// [`super::button_layout_from_wire`]).
+    pub const BUTTON_LAYOUT: u32 = 7;
+}
+
+/// Porta do serviço de sessão, que guarda a geometria das janelas com tag
+/// entre reinícios do compositor.
+pub const SESSION_PORT: &str = "firefly.session";
+
+/// Estados em `WindowMemoryRecord::state`.
+pub mod remembered_state {
+    /// Janela normal.
+    pub const NORMAL: u32 = 0;
+    /// Janela maximizada.
+    pub const MAXIMIZED: u32 = 1;
+    /// Janela minimizada.
+    pub const MINIMIZED: u32 = 2;
+}
+
+/// Fases de toque (`TouchUpdateRequest::phase` e `TouchEvent::phase`).
+pub mod touch_phase {
+    /// Dedo encostou.
+    pub const DOWN: u32 = 0;
+    /// Dedo moveu.
+    pub const MOVE: u32 = 1;
+    /// Dedo levantou.
+    pub const UP: u32 = 2;
+}
+
+/// Bit inicial do tipo de janela dentro de `CreateWindowRequest::flags`.
+///
+/// `CreateWindowRequest` não tem campo de tipo; o byte alto das flags
+/// (não usado por `WindowFlags`) carrega o [`WindowType`].
+pub const WINDOW_TYPE_SHIFT: u32 = 24;
+
+// =============================================================================
+// ESTRUTURAS
+// =============================================================================
+
 /// Request de input vindo do serviço de input.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -15,8 +189,778 @@ pub struct InputUpdateRequest {
     pub mouse_buttons: u32,
 }
 
+/// Atualização de toque vinda do serviço de input.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TouchUpdateRequest {
+    pub op: u32,
+    /// Identifica o dedo enquanto ele estiver encostado.
+    pub touch_id: u32,
+    /// Fase (ver [`touch_phase`]).
+    pub phase: u32,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Evento de toque enviado ao cliente (coordenadas locais da janela).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TouchEvent {
+    pub op: u32,
+    pub phase: u32,
+    pub touch_id: u32,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Evento de movimento do ponteiro enviado à janela do press enquanto um
+/// botão está pressionado (coordenadas locais, podem estar fora da janela).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PointerMotionEvent {
+    pub op: u32,
+    pub x: i32,
+    pub y: i32,
+    pub buttons: u32,
+}
+
 /// Porta de comunicação com um cliente.
 pub struct ClientPort {
     pub window_id: u32,
     pub port: redpowder::ipc::Port,
+    /// Nome da porta de resposta (identifica o cliente para cotas).
+    pub client: String,
+    /// Bytes de SHM alocados para a janela.
+    pub shm_bytes: usize,
+}
+
+impl ClientPort {
+    /// Retorna (janelas, bytes de SHM) em uso pelo cliente.
+    pub fn usage(client_ports: &[ClientPort], client: &str) -> (usize, usize) {
+        client_ports
+            .iter()
+            .filter(|c| c.client == client)
+            .fold((0, 0), |(count, bytes), c| (count + 1, bytes + c.shm_bytes))
+    }
+}
+
+/// Evento enviado ao cliente quando a geometria da janela muda.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigureEvent {
+    pub op: u32,
+    pub window_id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Request de notificação (toast).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct NotifyRequest {
+    pub op: u32,
+    /// ID definido pelo cliente, ecoado no evento de click.
+    pub notification_id: u32,
+    /// Tempo de exibição (ms).
+    pub duration_ms: u32,
+    pub title: [u8; 48],
+    pub body: [u8; 128],
+    /// Porta para o evento de click (vazia = sem evento).
+    pub reply_port: [u8; 32],
+}
+
+/// Evento enviado quando o usuário clica em uma notificação.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct NotifyClickedEvent {
+    pub op: u32,
+    pub notification_id: u32,
+}
+
+/// Request para alterar a opacidade global de uma janela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetOpacityRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Opacidade (0-255; valores maiores são limitados a 255).
+    pub opacity: u32,
+}
+
+/// Request para mover uma janela para outra camada.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetWindowLayerRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Camada (ver [`layer_from_wire`]).
+    pub layer: u32,
+}
+
+/// Request para alterar o modo do wallpaper.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetWallpaperModeRequest {
+    pub op: u32,
+    /// Modo (ver [`wallpaper_mode_from_wire`]).
+    pub mode: u32,
+}
+
+/// Request para copiar o buffer de uma janela para o wallpaper.
+///
+/// O cliente cria uma janela do tamanho da imagem (por exemplo na camada
+/// Background, invisível), desenha, faz commit e envia esta request; depois
+/// pode destruir a janela, pois o compositor guarda uma cópia.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetWallpaperFromWindowRequest {
+    pub op: u32,
+    pub window_id: u32,
+}
+
+/// Request para alterar o layout de teclado.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetKeymapRequest {
+    pub op: u32,
+    /// Layout (ver [`keymap_from_wire`]).
+    pub keymap: u32,
+}
+
+/// Request para mudar uma opção do compositor.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetCompositorOptionRequest {
+    pub op: u32,
+    /// Opção (ver [`compositor_option`]).
+    pub option: u32,
+    /// Novo valor.
+    pub value: u32,
+}
+
+/// Resposta a CREATE_WINDOW quando a cota do cliente foi excedida.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct QuotaExceededResponse {
+    pub op: u32,
+    /// Janelas já abertas pelo cliente.
+    pub window_count: u32,
+    /// Bytes de SHM já alocados pelo cliente.
+    pub shm_bytes: u64,
+}
+
+/// Request para forçar o redesenho completo de uma janela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RefreshWindowRequest {
+    pub op: u32,
+    pub window_id: u32,
+}
+
+/// Request para posicionar uma janela ancorada na janela pai.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetPositionRelativeRequest {
+    pub op: u32,
+    pub window_id: u32,
+    pub parent_id: u32,
+    /// Ponto de ancoragem, relativo à origem da janela pai.
+    pub anchor_x: i32,
+    pub anchor_y: i32,
+    /// Canto do popup preso ao anchor (ver [`gravity_from_wire`]).
+    pub gravity: u32,
+}
+
+/// Request para ler a cor composta em um ponto da tela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GetPixelRequest {
+    pub op: u32,
+    pub x: i32,
+    pub y: i32,
+    /// Porta para a resposta (C-string).
+    pub reply_port: [u8; 32],
+}
+
+/// Request para ativar (restaurar, trazer à frente e focar) a primeira
+/// janela com o título dado.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ActivateWindowByTitleRequest {
+    pub op: u32,
+    /// Título exato (C-string).
+    pub title: [u8; 64],
+    /// Porta para a resposta (C-string).
+    pub reply_port: [u8; 32],
+}
+
+/// Resposta a ACTIVATE_WINDOW_BY_TITLE.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct WindowActivatedResponse {
+    pub op: u32,
+    /// Janela ativada (0 = nenhum título igual).
+    pub window_id: u32,
+}
+
+/// Resposta a GET_PIXEL.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PixelResponse {
+    pub op: u32,
+    pub x: i32,
+    pub y: i32,
+    /// Cor ARGB8888 (0 se fora da tela).
+    pub color: u32,
+    /// 1 se (x, y) está dentro da tela.
+    pub valid: u32,
+}
+
+/// Request para consultar o heartbeat.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GetHeartbeatRequest {
+    pub op: u32,
+    /// Porta para a resposta (C-string).
+    pub reply_port: [u8; 32],
+}
+
+/// Resposta a GET_HEARTBEAT.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HeartbeatResponse {
+    pub op: u32,
+    /// Janelas existentes.
+    pub window_count: u32,
+    /// Frames apresentados por inteiro.
+    pub frames: u64,
+}
+
+/// Request para consultar estatísticas de renderização.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GetStatsRequest {
+    pub op: u32,
+    /// Porta para a resposta (C-string).
+    pub reply_port: [u8; 32],
+}
+
+/// Resposta a GET_STATS, com os números do último frame composto.
+///
+/// Overdraw = `pixels_written / damaged_pixels`; a fração da tela
+/// recomposta é `damaged_pixels / screen_pixels`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct StatsResponse {
+    pub op: u32,
+    /// Janelas existentes.
+    pub window_count: u32,
+    /// Pixels escritos no backbuffer.
+    pub pixels_written: u64,
+    /// Pixels da região danificada.
+    pub damaged_pixels: u64,
+    /// Pixels da tela.
+    pub screen_pixels: u64,
+}
+
+/// Request para executar o self-test do compositor.
+#[cfg(feature = "selftest")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SelfTestRequest {
+    pub op: u32,
+    /// Porta para a resposta (C-string).
+    pub reply_port: [u8; 32],
+}
+
+/// Self-test: preenchimento de retângulos.
+#[cfg(feature = "selftest")]
+pub const SELFTEST_FILL: u32 = 1 << 0;
+/// Self-test: cópia de pixels (blit).
+#[cfg(feature = "selftest")]
+pub const SELFTEST_BLIT: u32 = 1 << 1;
+/// Self-test: apresentação no display.
+#[cfg(feature = "selftest")]
+pub const SELFTEST_PRESENT: u32 = 1 << 2;
+
+/// Resposta a SELFTEST.
+#[cfg(feature = "selftest")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SelfTestResponse {
+    pub op: u32,
+    /// Operações que passaram (`SELFTEST_*`).
+    pub passed: u32,
+    /// Operações que falharam (`SELFTEST_*`).
+    pub failed: u32,
+}
+
+/// Máximo de retângulos em uma região de input.
+pub const MAX_INPUT_REGION_RECTS: usize = 8;
+
+/// Retângulo no protocolo.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct WireRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Request para definir a região de input (coordenadas locais da janela).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetInputRegionRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Retângulos válidos em `rects` (0 = janela inteira).
+    pub count: u32,
+    pub rects: [WireRect; MAX_INPUT_REGION_RECTS],
+}
+
+/// Request para declarar a região opaca (coordenadas locais da janela).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetOpaqueRegionRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Região opaca (largura ou altura 0 remove a região).
+    pub rect: WireRect,
+}
+
+/// Request para definir a tag estável da janela (ex.: "editor.main").
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetWindowTagRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Tag terminada em zero (vazia remove a tag).
+    pub tag: [u8; 32],
+}
+
+/// Geometria lembrada de uma janela com tag, trocada com o serviço de
+/// sessão ([`SESSION_PORT`]).
+///
+/// O compositor envia com `EVENT_WINDOW_REMEMBERED` quando a janela é
+/// destruída; o serviço devolve com `LOAD_WINDOW_MEMORY` após receber
+/// `EVENT_SESSION_RESTORE` (ou ao iniciar, se o compositor já estava no ar).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct WindowMemoryRecord {
+    pub op: u32,
+    /// Tag terminada em zero.
+    pub tag: [u8; 32],
+    /// Geometria normal (fora da maximização/minimização).
+    pub rect: WireRect,
+    /// Estado (ver [`remembered_state`]).
+    pub state: u32,
+}
+
+/// Evento sem dados além do opcode.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SessionRestoreEvent {
+    pub op: u32,
+}
+
+/// Request para entrar ou sair da tela cheia.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetFullscreenRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// 1 = tela cheia, 0 = restaurar.
+    pub enabled: u32,
+}
+
+/// Bit de [`SetConstraintsRequest::flags`]: o usuário não pode mover a janela.
+pub const CONSTRAINT_NO_MOVE: u32 = 1 << 0;
+/// Bit de [`SetConstraintsRequest::flags`]: o usuário não pode redimensionar
+/// nem maximizar a janela.
+pub const CONSTRAINT_NO_RESIZE: u32 = 1 << 1;
+
+/// Request para restringir as interações do usuário com a janela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetConstraintsRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Combinação de `CONSTRAINT_*` (0 remove as restrições).
+    pub flags: u32,
+}
+
+/// Maior fator de escala por janela.
+pub const MAX_WINDOW_SCALE: u32 = 4;
+
+/// Request para definir o fator de escala da janela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetWindowScaleRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// 1 a [`MAX_WINDOW_SCALE`].
+    pub scale: u32,
+}
+
+/// Request para declarar a janela mãe de um diálogo (transient).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetTransientForRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Janela mãe (0 = nenhuma).
+    pub parent_id: u32,
+}
+
+/// Request para recompor uma área arbitrária da tela.
+///
+/// Sem restrição: o IPC não identifica o remetente, então qualquer cliente
+/// pode pedir. Só repinta o que já está na cena, sem ler nem alterar
+/// conteúdo de outras janelas.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DamageRegionRequest {
+    pub op: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Request para enrolar ou desenrolar a janela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ShadeWindowRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// 1 = enrolar, 0 = desenrolar.
+    pub shaded: u32,
+}
+
+/// Request para exibir o texto de composição (vazio remove).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SetPreeditRequest {
+    pub op: u32,
+    pub window_id: u32,
+    /// Posição do cursor de texto (coordenadas da janela).
+    pub cursor_x: i32,
+    pub cursor_y: i32,
+    /// Texto UTF-8 terminado em zero.
+    pub text: [u8; 64],
+}
+
+/// Máximo de buffers no anel de captura.
+pub const MAX_CAPTURE_BUFFERS: usize = 4;
+
+/// Request para iniciar a captura (`buffer_count == 0` para).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct StartCaptureRequest {
+    pub op: u32,
+    /// Buffers no anel (até [`MAX_CAPTURE_BUFFERS`]).
+    pub buffer_count: u32,
+    /// Porta para a resposta e os eventos de frame.
+    pub reply_port: [u8; 32],
+}
+
+/// Resposta com o anel de captura alocado pelo compositor.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CaptureStartedResponse {
+    pub op: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Buffers válidos em `shm_handles`.
+    pub buffer_count: u32,
+    pub shm_handles: [u64; MAX_CAPTURE_BUFFERS],
+}
+
+/// Evento: frame copiado para `index`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CaptureFrameEvent {
+    pub op: u32,
+    pub index: u32,
+    /// Instante do frame (ms desde o início do compositor).
+    pub frame_time_ms: u64,
+    /// Frames descartados até agora (gravador atrasado).
+    pub dropped: u32,
+}
+
+/// Request para devolver um buffer de captura.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CaptureReleaseRequest {
+    pub op: u32,
+    pub index: u32,
+}
+
+/// Request de frame callback (vale para um único frame).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RequestFrameRequest {
+    pub op: u32,
+    pub window_id: u32,
+}
+
+/// Evento enviado ao cliente de um popup (`WindowType::Menu`) quando o
+/// usuário clica fora dele. O cliente deve destruir ou esconder o popup.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PopupDismissedEvent {
+    pub op: u32,
+    pub window_id: u32,
+}
+
+/// Evento enviado após o frame que incluiu a janela.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameDoneEvent {
+    pub op: u32,
+    pub window_id: u32,
+    /// Instante do frame (ms desde o início do compositor).
+    pub frame_time_ms: u64,
+}
+
+// =============================================================================
+// DECODIFICAÇÃO
+// =============================================================================
+
+/// Request com opcode fixo.
+///
+/// Toda request é `#[repr(C)]` com `op: u32` no offset 0 (verificado em
+/// tempo de compilação em [`request_opcodes!`]).
+pub trait Request: Sized {
+    /// Opcode que a request deve carregar em `op`.
+    const OPCODE: u32;
+}
+
+/// Associa cada request ao seu opcode e garante `op` no offset 0.
+macro_rules! request_opcodes {
+    ($($ty:ty => $opcode:expr),* $(,)?) => {
+        $(
+            impl Request for $ty {
+                const OPCODE: u32 = $opcode;
+            }
+            const _: () = assert!(core::mem::offset_of!($ty, op) == 0);
+        )*
+    };
+}
+
+request_opcodes! {
+    CreateWindowRequest => opcodes::CREATE_WINDOW,
+    CommitBufferRequest => opcodes::COMMIT_BUFFER,
+    DestroyWindowRequest => opcodes::DESTROY_WINDOW,
+    RegisterTaskbarRequest => opcodes::REGISTER_TASKBAR,
+    InputUpdateRequest => opcodes::INPUT_UPDATE,
+    TouchUpdateRequest => ext_opcodes::TOUCH_UPDATE,
+    NotifyRequest => ext_opcodes::NOTIFY,
+    SetOpacityRequest => ext_opcodes::SET_OPACITY,
+    SetWindowLayerRequest => ext_opcodes::SET_WINDOW_LAYER,
+    SetWallpaperModeRequest => ext_opcodes::SET_WALLPAPER_MODE,
+    SetWallpaperFromWindowRequest => ext_opcodes::SET_WALLPAPER_FROM_WINDOW,
+    SetKeymapRequest => ext_opcodes::SET_KEYMAP,
+    SetCompositorOptionRequest => ext_opcodes::SET_COMPOSITOR_OPTION,
+    RefreshWindowRequest => ext_opcodes::REFRESH_WINDOW,
+    SetPositionRelativeRequest => ext_opcodes::SET_POSITION_RELATIVE,
+    GetPixelRequest => ext_opcodes::GET_PIXEL,
+    ActivateWindowByTitleRequest => ext_opcodes::ACTIVATE_WINDOW_BY_TITLE,
+    GetHeartbeatRequest => ext_opcodes::GET_HEARTBEAT,
+    GetStatsRequest => ext_opcodes::GET_STATS,
+    SetInputRegionRequest => ext_opcodes::SET_INPUT_REGION,
+    SetOpaqueRegionRequest => ext_opcodes::SET_OPAQUE_REGION,
+    SetWindowTagRequest => ext_opcodes::SET_WINDOW_TAG,
+    WindowMemoryRecord => ext_opcodes::LOAD_WINDOW_MEMORY,
+    SetFullscreenRequest => ext_opcodes::SET_FULLSCREEN,
+    SetConstraintsRequest => ext_opcodes::SET_CONSTRAINTS,
+    SetPreeditRequest => ext_opcodes::SET_PREEDIT,
+    ShadeWindowRequest => ext_opcodes::SHADE_WINDOW,
+    DamageRegionRequest => ext_opcodes::DAMAGE_REGION,
+    SetWindowScaleRequest => ext_opcodes::SET_WINDOW_SCALE,
+    SetTransientForRequest => ext_opcodes::SET_TRANSIENT_FOR,
+    StartCaptureRequest => ext_opcodes::START_CAPTURE,
+    CaptureReleaseRequest => ext_opcodes::CAPTURE_RELEASE,
+    RequestFrameRequest => ext_opcodes::REQUEST_FRAME,
+}
+
+#[cfg(feature = "selftest")]
+request_opcodes! {
+    SelfTestRequest => ext_opcodes::SELFTEST,
+}
+
+// WindowOpRequest serve a vários opcodes (ver `decode_as`)
+const _: () = assert!(core::mem::offset_of!(WindowOpRequest, op) == 0);
+
+/// Decodifica uma request, validando tamanho e `op`.
+pub fn decode<T: Request>(data: &[u8]) -> Option<&T> {
+    decode_as(data, T::OPCODE)
+}
+
+/// Decodifica `T` esperando `op == opcode` (para structs compartilhadas
+/// por vários opcodes).
+pub fn decode_as<T>(data: &[u8], opcode: u32) -> Option<&T> {
+    if data.len() < core::mem::size_of::<T>() {
+        return None;
+    }
+
+    let op = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]);
+    if op != opcode {
+        redpowder::println!(
+            "[Firefly] Request com op {:#x} onde se esperava {:#x}",
+            op,
+            opcode
+        );
+        return None;
+    }
+
+    Some(unsafe { &*(data.as_ptr() as *const T) })
+}
+
+/// Valor de camada para widgets de desktop: `Background`, acima do
+/// wallpaper e abaixo de `Normal` (não tem `LayerType` próprio).
+pub const DESKTOP_WIDGET_LAYER: u32 = 7;
+
+/// Converte o valor de camada do protocolo.
+///
+/// 0 = Background, 1 = Normal, 2 = Top, 3 = Panel, 4 = Overlay, 5 = Lock,
+/// 6 = Cursor. Widgets de desktop usam [`DESKTOP_WIDGET_LAYER`].
+pub fn layer_from_wire(value: u32) -> Option<LayerType> {
+    match value {
+        0 => Some(LayerType::Background),
+        1 => Some(LayerType::Normal),
+        2 => Some(LayerType::Top),
+        3 => Some(LayerType::Panel),
+        4 => Some(LayerType::Overlay),
+        5 => Some(LayerType::Lock),
+        6 => Some(LayerType::Cursor),
+        _ => None,
+    }
+}
+
+/// Converte o modo de wallpaper do protocolo.
+///
+/// 0 = Center, 1 = Tile, 2 = Stretch, 3 = Fill.
+pub fn wallpaper_mode_from_wire(value: u32) -> Option<WallpaperMode> {
+    match value {
+        0 => Some(WallpaperMode::Center),
+        1 => Some(WallpaperMode::Tile),
+        2 => Some(WallpaperMode::Stretch),
+        3 => Some(WallpaperMode::Fill),
+        _ => None,
+    }
+}
+
+/// Converte a curva de animação do protocolo.
+///
+/// 0 = Linear, 1 = EaseInOut, 2 = EaseOutCubic.
+pub fn easing_from_wire(value: u32) -> Option<Easing> {
+    match value {
+        0 => Some(Easing::Linear),
+        1 => Some(Easing::EaseInOut),
+        2 => Some(Easing::EaseOutCubic),
+        _ => None,
+    }
+}
+
+/// Converte a disposição dos botões da barra de título do protocolo.
+///
+/// Bit 0 = lado (0 = direita, 1 = esquerda). A partir do bit 4, até três
+/// campos de 2 bits com os botões, do mais externo para o mais interno:
+/// 1 = Fechar, 2 = Minimizar, 3 = Maximizar, 0 = fim da lista. Botão
+/// repetido é inválido.
+pub fn button_layout_from_wire(value: u32) -> Option<ButtonLayout> {
+    let side = if value & 1 != 0 {
+        ButtonSide::Left
+    } else {
+        ButtonSide::Right
+    };
+
+    let mut order = Vec::new();
+    for i in 0..3 {
+        let button = match (value >> (4 + i * 2)) & 0b11 {
+            0 => break,
+            1 => TitlebarButton::Close,
+            2 => TitlebarButton::Minimize,
+            _ => TitlebarButton::Maximize,
+        };
+        if order.contains(&button) {
+            return None;
+        }
+        order.push(button);
+    }
+
+    Some(ButtonLayout { side, order })
+}
+
+/// Converte o layout de teclado do protocolo.
+///
+/// 0 = US QWERTY.
+pub fn keymap_from_wire(value: u32) -> Option<Keymap> {
+    match value {
+        0 => Some(Keymap::UsQwerty),
+        _ => None,
+    }
+}
+
+/// Extrai o tipo de janela das flags de CREATE_WINDOW.
+///
+/// 0 = Normal, 1 = Dialog, 2 = Tooltip, 3 = Menu, 4 = Dock, 5 = Splash.
+/// Valores desconhecidos viram `Normal`.
+pub fn window_type_from_flags(flags: u32) -> WindowType {
+    match flags >> WINDOW_TYPE_SHIFT {
+        1 => WindowType::Dialog,
+        2 => WindowType::Tooltip,
+        3 => WindowType::Menu,
+        4 => WindowType::Dock,
+        5 => WindowType::Splash,
+        _ => WindowType::Normal,
+    }
+}
+
+/// Converte a gravidade do protocolo.
+///
+/// 0 = TopLeft, 1 = TopRight, 2 = BottomLeft, 3 = BottomRight.
+pub fn gravity_from_wire(value: u32) -> Option<Gravity> {
+    match value {
+        0 => Some(Gravity::TopLeft),
+        1 => Some(Gravity::TopRight),
+        2 => Some(Gravity::BottomLeft),
+        3 => Some(Gravity::BottomRight),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// STRINGS
+// =============================================================================
+
+/// Tamanho do campo de título no protocolo (bytes).
+pub const MAX_TITLE_BYTES: usize = 64;
+
+/// Lê uma string terminada em zero.
+///
+/// Bytes UTF-8 inválidos no fim (caractere cortado pelo cliente) são
+/// descartados; o prefixo válido é mantido.
+pub fn c_str(buf: &[u8]) -> &str {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    match core::str::from_utf8(&buf[..len]) {
+        Ok(s) => s,
+        Err(e) => core::str::from_utf8(&buf[..e.valid_up_to()]).unwrap_or(""),
+    }
+}
+
+/// Corta `s` em no máximo `max_bytes` sem dividir um caractere.
+pub fn truncate_title(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0);
+    &s[..end]
 }
@@ -0,0 +1,135 @@
+//! # Registry
+//!
+//! Registro de opcodes: a lista completa de requests que o compositor
+//! aceita, cada uma com o tamanho mínimo da mensagem e o handler no
+//! [`Server`].
+//!
+//! Para aceitar um opcode novo: declare a request em `protocol` (com
+//! `request_opcodes!`), escreva o `Server::on_*` e adicione uma linha em
+//! [`OPCODES`]. Opcodes repetidos não compilam.
+
+use redpowder::syscall::SysResult;
+use redpowder::window::{
+    opcodes, CommitBufferRequest, CreateWindowRequest, DestroyWindowRequest,
+    RegisterTaskbarRequest, WindowOpRequest,
+};
+
+#[cfg(feature = "selftest")]
+use super::protocol::SelfTestRequest;
+use super::protocol::{
+    ActivateWindowByTitleRequest, CaptureReleaseRequest, DamageRegionRequest, GetHeartbeatRequest,
+    GetPixelRequest, GetStatsRequest, InputUpdateRequest, NotifyRequest, RefreshWindowRequest,
+    Request, RequestFrameRequest, SetCompositorOptionRequest, SetConstraintsRequest,
+    SetFullscreenRequest, SetInputRegionRequest, SetKeymapRequest, SetOpacityRequest,
+    SetOpaqueRegionRequest, SetPositionRelativeRequest, SetPreeditRequest, SetTransientForRequest,
+    SetWallpaperFromWindowRequest, SetWallpaperModeRequest, SetWindowLayerRequest,
+    SetWindowScaleRequest, SetWindowTagRequest, ShadeWindowRequest, StartCaptureRequest,
+    TouchUpdateRequest, WindowMemoryRecord,
+};
+use super::server::Server;
+
+// =============================================================================
+// ENTRADAS
+// =============================================================================
+
+/// Handler de um opcode (recebe a mensagem inteira).
+pub type Handler = fn(&mut Server, &[u8]) -> SysResult<()>;
+
+/// Opcode registrado.
+pub struct OpcodeEntry {
+    /// Valor do opcode.
+    pub opcode: u32,
+    /// Nome para o log.
+    pub name: &'static str,
+    /// Tamanho mínimo da mensagem (a struct da request).
+    pub min_len: usize,
+    /// Handler no servidor.
+    pub handler: Handler,
+}
+
+/// Entrada de uma request com opcode fixo (ver [`Request`]).
+macro_rules! entry {
+    ($ty:ty, $handler:ident) => {
+        entry!(<$ty as Request>::OPCODE, $ty, $handler)
+    };
+    ($opcode:expr, $ty:ty, $handler:ident) => {
+        OpcodeEntry {
+            opcode: $opcode,
+            name: stringify!($ty),
+            min_len: core::mem::size_of::<$ty>(),
+            handler: Server::$handler,
+        }
+    };
+}
+
+/// Todos os opcodes aceitos pelo compositor.
+pub const OPCODES: &[OpcodeEntry] = &[
+    // Protocolo base (redpowder)
+    entry!(CreateWindowRequest, on_create_window),
+    entry!(CommitBufferRequest, on_commit_buffer),
+    entry!(DestroyWindowRequest, on_destroy_window),
+    entry!(InputUpdateRequest, on_input_update),
+    entry!(
+        opcodes::MINIMIZE_WINDOW,
+        WindowOpRequest,
+        on_minimize_window
+    ),
+    entry!(opcodes::RESTORE_WINDOW, WindowOpRequest, on_restore_window),
+    entry!(RegisterTaskbarRequest, on_register_taskbar),
+    // Extensões do Firefly
+    entry!(NotifyRequest, on_notify),
+    entry!(SetOpacityRequest, on_set_opacity),
+    entry!(SetWindowLayerRequest, on_set_window_layer),
+    entry!(SetWallpaperModeRequest, on_set_wallpaper_mode),
+    entry!(SetWallpaperFromWindowRequest, on_set_wallpaper_from_window),
+    entry!(SetKeymapRequest, on_set_keymap),
+    entry!(SetCompositorOptionRequest, on_set_compositor_option),
+    entry!(RefreshWindowRequest, on_refresh_window),
+    entry!(SetPositionRelativeRequest, on_set_position_relative),
+    entry!(GetPixelRequest, on_get_pixel),
+    entry!(TouchUpdateRequest, on_touch_update),
+    entry!(RequestFrameRequest, on_request_frame),
+    entry!(GetHeartbeatRequest, on_get_heartbeat),
+    entry!(SetInputRegionRequest, on_set_input_region),
+    entry!(SetOpaqueRegionRequest, on_set_opaque_region),
+    entry!(SetWindowTagRequest, on_set_window_tag),
+    entry!(WindowMemoryRecord, on_load_window_memory),
+    entry!(SetFullscreenRequest, on_set_fullscreen),
+    entry!(StartCaptureRequest, on_start_capture),
+    entry!(CaptureReleaseRequest, on_capture_release),
+    entry!(SetConstraintsRequest, on_set_constraints),
+    entry!(SetPreeditRequest, on_set_preedit),
+    entry!(ShadeWindowRequest, on_shade_window),
+    entry!(DamageRegionRequest, on_damage_region),
+    entry!(SetWindowScaleRequest, on_set_window_scale),
+    entry!(ActivateWindowByTitleRequest, on_activate_window_by_title),
+    entry!(SetTransientForRequest, on_set_transient_for),
+    entry!(GetStatsRequest, on_get_stats),
+    #[cfg(feature = "selftest")]
+    entry!(SelfTestRequest, on_self_test),
+];
+
+// Opcodes repetidos fariam uma das entradas nunca ser despachada
+const _: () = {
+    let mut i = 0;
+    while i < OPCODES.len() {
+        let mut j = i + 1;
+        while j < OPCODES.len() {
+            assert!(
+                OPCODES[i].opcode != OPCODES[j].opcode,
+                "opcode registrado duas vezes"
+            );
+            j += 1;
+        }
+        i += 1;
+    }
+};
+
+// =============================================================================
+// BUSCA
+// =============================================================================
+
+/// Entrada registrada para `opcode`.
+pub fn lookup(opcode: u32) -> Option<&'static OpcodeEntry> {
+    OPCODES.iter().find(|entry| entry.opcode == opcode)
+}
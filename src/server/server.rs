@@ -4,7 +4,8 @@
 
 use alloc::vec::Vec;
 use gfx_types::display::DisplayInfo;
-use gfx_types::window::LayerType;
+use gfx_types::geometry::Point;
+use gfx_types::window::{LayerType, WindowState};
 use redpowder::graphics::get_info;
 use redpowder::ipc::Port;
 use redpowder::syscall::SysResult;
@@ -13,13 +14,29 @@ use redpowder::window::{
     COMPOSITOR_PORT, MAX_MSG_SIZE,
 };
 
-use crate::input::InputManager;
+use crate::input::keymap::Keymap;
+use crate::input::{scancode, InputManager};
 use crate::render::RenderEngine;
+use crate::scene::placement::keep_titlebar_reachable;
+use crate::scene::WindowType;
+use crate::ui::decoration::{ButtonLayout, TitlebarButton, TITLEBAR_HEIGHT};
+use crate::ui::window_menu::{WindowMenu, WindowMenuItem};
 
-use super::dispatch::{dispatch_key_event, dispatch_mouse_event, send_lifecycle_event};
+use super::dispatch::{
+    dispatch_capture_frame, dispatch_configure_event, dispatch_frame_done, dispatch_key_event,
+    dispatch_mouse_event, dispatch_pointer_motion, dispatch_popup_dismissed, dispatch_touch_event,
+    send_lifecycle_event, send_notify_clicked, send_session_restore, send_window_remembered,
+};
 use super::handlers;
-use super::protocol::{ClientPort, InputUpdateRequest};
-use super::state::{ClickState, DragState, MouseState};
+use super::idle::IdleManager;
+use super::protocol::{
+    decode, decode_as, touch_phase, ClientPort, InputUpdateRequest, TouchUpdateRequest,
+};
+use super::registry;
+use super::state::{
+    ClickState, ClientQuota, DragState, Heartbeat, KeyboardMoveState, ModifierState, PendingReply,
+    RememberedWindow, TouchState, WindowMemory,
+};
 
 // =============================================================================
 // CONSTANTES
@@ -28,6 +45,27 @@ use super::state::{ClickState, DragState, MouseState};
 /// Intervalo entre frames (ms) - ~60 FPS.
 const FRAME_INTERVAL_MS: u64 = 16;
 
+/// Máximo de mensagens IPC processadas antes de cada frame.
+const MAX_MESSAGES_PER_FRAME: usize = 64;
+
+/// Pixels da barra de título mantidos na tela durante o arraste.
+const DRAG_MIN_VISIBLE: u32 = 32;
+
+/// Intervalo (em iterações do loop) entre consultas do modo do display.
+const DISPLAY_POLL_INTERVAL: u64 = 60;
+
+/// Passo do modo de mover pelo teclado (px).
+const KEYBOARD_MOVE_STEP: i32 = 10;
+
+/// Passo do modo de mover pelo teclado com Shift (px).
+const KEYBOARD_MOVE_STEP_LARGE: i32 = 50;
+
+/// Tentativas de ler o modo do display na inicialização.
+const DISPLAY_INIT_ATTEMPTS: u32 = 5;
+
+/// Espera antes da primeira nova tentativa (ms); dobra a cada falha.
+const DISPLAY_INIT_BACKOFF_MS: u64 = 100;
+
 // =============================================================================
 // SERVER
 // =============================================================================
@@ -46,16 +84,47 @@ pub struct Server {
     frame_count: u64,
     /// Portas de clientes conectados.
     client_ports: Vec<ClientPort>,
+    /// Respostas aguardando a porta do cliente.
+    pending_replies: Vec<PendingReply>,
     /// Janela com foco.
     focused_window: Option<u32>,
-    /// Estado do mouse.
-    mouse: MouseState,
     /// Estado de arraste.
     drag: DragState,
     /// Estado de click.
     click: ClickState,
+    /// Janela que recebeu o press (grab implícito até o release).
+    press_target: Option<u32>,
+    /// Estado das teclas modificadoras.
+    modifiers: ModifierState,
+    /// Modo de mover janela pelo teclado.
+    keyboard_move: KeyboardMoveState,
+    /// Layout de teclado ativo.
+    keymap: Keymap,
+    /// Cotas de recursos por cliente.
+    quota: ClientQuota,
+    /// Toques ativos.
+    touch: TouchState,
+    /// Disposição dos botões da barra de título.
+    button_layout: ButtonLayout,
+    /// Toque primário gera eventos de mouse (apps sem suporte a toque;
+    /// desligado por padrão, ver `compositor_option::TOUCH_EMULATES_MOUSE`).
+    touch_emulates_mouse: bool,
     /// Porta da taskbar.
     taskbar_port: Option<Port>,
+    /// Progresso do loop principal (para supervisores).
+    heartbeat: Heartbeat,
+    /// Escurecimento da tela por inatividade.
+    idle: IdleManager,
+    /// Posição lembrada das janelas com tag.
+    window_memory: WindowMemory,
+    /// Pixels da barra de título que o arraste mantém na tela.
+    drag_min_visible: u32,
+    /// Mensagens ficaram na fila no último frame (limite atingido).
+    message_backlog: bool,
+    /// Porta do gravador de tela (captura ativa).
+    capture_port: Option<Port>,
+    /// Popups abertos (`WindowType::Menu`), do mais antigo ao mais novo.
+    popups: Vec<u32>,
 }
 
 impl Server {
@@ -71,26 +140,21 @@ impl Server {
 
         // 2. Obter informações do display
         let _ = redpowder::console::write_str("[Firefly] Obtendo info display...\n");
-        let fb_info = get_info()?;
+        // No boot o serviço de framebuffer do kernel pode ainda não estar pronto
+        let display_info = retry_with_backoff(
+            DISPLAY_INIT_ATTEMPTS,
+            DISPLAY_INIT_BACKOFF_MS,
+            query_display_info,
+        )?;
         let _ = redpowder::console::write_str("[Firefly] Display info OK\n");
         redpowder::println!(
             "[Firefly] Display: {}x{} stride={}",
-            fb_info.width,
-            fb_info.height,
-            fb_info.stride
+            display_info.width,
+            display_info.height,
+            display_info.stride / 4
         );
 
-        // 3. Criar DisplayInfo para gfx_types
-        let display_info = DisplayInfo {
-            id: 0,
-            width: fb_info.width,
-            height: fb_info.height,
-            refresh_rate_mhz: 60_000,
-            format: gfx_types::color::PixelFormat::ARGB8888,
-            stride: fb_info.stride * 4,
-        };
-
-        // 4. Criar motor de renderização
+        // 3. Criar motor de renderização
         let render_engine = RenderEngine::new(display_info);
 
         Ok(Self {
@@ -100,11 +164,26 @@ impl Server {
             running: true,
             frame_count: 0,
             client_ports: Vec::new(),
+            pending_replies: Vec::new(),
             focused_window: None,
-            mouse: MouseState::new(),
             drag: DragState::new(),
             click: ClickState::new(),
+            press_target: None,
+            modifiers: ModifierState::new(),
+            keyboard_move: KeyboardMoveState::new(),
+            keymap: Keymap::default(),
+            quota: ClientQuota::new(),
+            touch: TouchState::new(),
+            button_layout: ButtonLayout::default(),
+            touch_emulates_mouse: false,
             taskbar_port: None,
+            heartbeat: Heartbeat::new(),
+            idle: IdleManager::new(),
+            window_memory: WindowMemory::new(),
+            drag_min_visible: DRAG_MIN_VISIBLE,
+            message_backlog: false,
+            capture_port: None,
+            popups: Vec::new(),
         })
     }
 
@@ -114,6 +193,7 @@ impl Server {
         let mut loop_count = 0u64;
 
         redpowder::println!("[Firefly] Entrando no loop principal");
+        send_session_restore();
 
         while self.running {
             loop_count += 1;
@@ -129,11 +209,43 @@ impl Server {
                 );
             }
 
+            // Detectar mudança de resolução
+            if loop_count % DISPLAY_POLL_INTERVAL == 0 {
+                self.poll_display_mode();
+            }
+
             // 1. Processar mensagens IPC
             self.process_messages(&mut msg_buf)?;
+            self.flush_pending_motion()?;
+            let now_ms = self.now_ms();
+            handlers::retry_pending_replies(
+                &mut self.client_ports,
+                &mut self.pending_replies,
+                now_ms,
+            );
 
             // 2. Renderizar frame
-            self.render_engine.render(self.mouse.x, self.mouse.y)?;
+            self.render_engine.update_notifications(self.now_ms());
+            self.render_engine
+                .set_idle_dim(self.idle.dim_level(self.now_ms()));
+            let presented = self
+                .render_engine
+                .render(self.input.pointer.x, self.input.pointer.y)?;
+            if presented {
+                self.heartbeat.beat();
+            }
+            let frame_time_ms = self.now_ms();
+            for window_id in self.render_engine.take_frames_done() {
+                dispatch_frame_done(&self.client_ports, window_id, frame_time_ms);
+            }
+            for frame in self.render_engine.take_captured_frames() {
+                if let Some(port) = &self.capture_port {
+                    dispatch_capture_frame(port, frame.index, frame_time_ms, frame.dropped);
+                }
+            }
+            for window_id in self.render_engine.take_lost_windows() {
+                self.destroy_window(window_id);
+            }
             self.frame_count += 1;
 
             // 3. Estabilizar framerate
@@ -143,21 +255,63 @@ impl Server {
         Ok(())
     }
 
+    /// Consulta o modo atual do display e reconfigura se mudou.
+    fn poll_display_mode(&mut self) {
+        let display_info = match query_display_info() {
+            Ok(info) => info,
+            Err(_) => return,
+        };
+
+        let current = self.render_engine.display_info();
+        if display_info.width == current.width
+            && display_info.height == current.height
+            && display_info.stride == current.stride
+        {
+            return;
+        }
+
+        for window_id in self.render_engine.reconfigure_display(display_info) {
+            if let Some(win) = self.render_engine.get_window(window_id) {
+                dispatch_configure_event(&self.client_ports, window_id, win.rect());
+            }
+        }
+    }
+
     // =========================================================================
     // PROCESSAMENTO DE MENSAGENS
     // =========================================================================
 
+    /// Processa até `MAX_MESSAGES_PER_FRAME` mensagens.
+    ///
+    /// O restante fica na fila da porta para o próximo frame, para que um
+    /// cliente inundando o compositor não impeça a renderização.
     fn process_messages(&mut self, buf: &mut [u8; MAX_MSG_SIZE]) -> SysResult<()> {
-        while let Ok(size) = self.port.recv(buf, 0) {
-            if size > 0 {
-                self.handle_message(&buf[..size])?;
-            } else {
-                break;
+        let mut processed = 0;
+        while processed < MAX_MESSAGES_PER_FRAME {
+            match self.port.recv(buf, 0) {
+                Ok(size) if size > 0 => self.handle_message(&buf[..size])?,
+                _ => {
+                    self.message_backlog = false;
+                    return Ok(());
+                }
             }
+            processed += 1;
+        }
+
+        if !self.message_backlog {
+            redpowder::println!(
+                "[Firefly] Limite de {} mensagens por frame atingido",
+                MAX_MESSAGES_PER_FRAME
+            );
         }
+        self.message_backlog = true;
         Ok(())
     }
 
+    /// Despacha uma mensagem pelo registro de opcodes.
+    ///
+    /// Opcodes desconhecidos e mensagens menores que a request registrada
+    /// são registrados no log e ignorados.
     fn handle_message(&mut self, data: &[u8]) -> SysResult<()> {
         if data.len() < 4 {
             return Ok(());
@@ -165,129 +319,650 @@ impl Server {
 
         let opcode = unsafe { *(data.as_ptr() as *const u32) };
 
-        match opcode {
-            opcodes::CREATE_WINDOW => {
-                let (window_id, layer) = handlers::handle_create_window(
-                    &mut self.render_engine,
-                    &mut self.client_ports,
-                    self.taskbar_port.as_ref(),
-                    data,
-                )?;
-
-                // Focar (se não for background)
-                if layer != LayerType::Background {
-                    self.focused_window = Some(window_id);
-                    self.render_engine.set_focus(Some(window_id));
-                }
-            }
-            opcodes::COMMIT_BUFFER => {
-                handlers::handle_commit_buffer(&mut self.render_engine, data);
-            }
-            opcodes::DESTROY_WINDOW => {
-                let req = unsafe { &*(data.as_ptr() as *const DestroyWindowRequest) };
-                if self.focused_window == Some(req.window_id) {
-                    self.focused_window = None;
-                    self.render_engine.set_focus(None);
-                }
-                handlers::handle_destroy_window(
-                    &mut self.render_engine,
-                    &mut self.client_ports,
-                    self.taskbar_port.as_ref(),
-                    req.window_id,
-                );
+        let entry = match registry::lookup(opcode) {
+            Some(entry) => entry,
+            None => {
+                redpowder::println!("[Firefly] Opcode desconhecido: {:#x}", opcode);
+                return Ok(());
             }
-            opcodes::INPUT_UPDATE => {
-                self.handle_input_update(data)?;
+        };
+        if data.len() < entry.min_len {
+            redpowder::println!(
+                "[Firefly] {} truncada: {} de {} bytes",
+                entry.name,
+                data.len(),
+                entry.min_len
+            );
+            return Ok(());
+        }
+
+        (entry.handler)(self, data)
+    }
+
+    // =========================================================================
+    // HANDLERS DE OPCODE (ver `registry`)
+    // =========================================================================
+
+    pub(super) fn on_create_window(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        let created = handlers::handle_create_window(
+            &mut self.render_engine,
+            &mut self.client_ports,
+            &mut self.pending_replies,
+            self.taskbar_port.as_ref(),
+            &self.quota,
+            data,
+            now_ms,
+        )?;
+
+        // Focar (exceto background e painéis)
+        if let Some((window_id, layer)) = created {
+            if self
+                .render_engine
+                .get_window(window_id)
+                .is_some_and(|win| win.window_type == WindowType::Menu)
+            {
+                self.popups.push(window_id);
             }
-            opcodes::MINIMIZE_WINDOW => {
-                let req = unsafe { &*(data.as_ptr() as *const WindowOpRequest) };
-                handlers::handle_minimize_window(
-                    &mut self.render_engine,
-                    self.taskbar_port.as_ref(),
-                    req.window_id,
-                );
+            if !matches!(layer, LayerType::Background | LayerType::Panel) {
+                self.focused_window = Some(window_id);
+                self.render_engine.set_focus(Some(window_id));
             }
-            opcodes::RESTORE_WINDOW => {
-                let req = unsafe { &*(data.as_ptr() as *const WindowOpRequest) };
-                if let Some(window_id) = handlers::handle_restore_window(
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_commit_buffer(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_commit_buffer(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_destroy_window(&mut self, data: &[u8]) -> SysResult<()> {
+        if let Some(req) = decode::<DestroyWindowRequest>(data) {
+            self.destroy_window(req.window_id);
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_input_update(&mut self, data: &[u8]) -> SysResult<()> {
+        self.handle_input_update(data)
+    }
+
+    pub(super) fn on_minimize_window(&mut self, data: &[u8]) -> SysResult<()> {
+        if let Some(req) = decode_as::<WindowOpRequest>(data, opcodes::MINIMIZE_WINDOW) {
+            self.minimize_window(req.window_id);
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_restore_window(&mut self, data: &[u8]) -> SysResult<()> {
+        let restored =
+            decode_as::<WindowOpRequest>(data, opcodes::RESTORE_WINDOW).and_then(|req| {
+                handlers::handle_restore_window(
                     &mut self.render_engine,
                     self.taskbar_port.as_ref(),
                     req.window_id,
-                ) {
-                    self.focused_window = Some(window_id);
-                    self.render_engine.set_focus(Some(window_id));
-                }
-            }
-            opcodes::REGISTER_TASKBAR => {
-                let req = unsafe { &*(data.as_ptr() as *const RegisterTaskbarRequest) };
-                if let Some(port) = handlers::handle_register_taskbar(req) {
-                    self.taskbar_port = Some(port);
-                }
-            }
-            _ => {
-                redpowder::println!("[Firefly] Opcode desconhecido: {:#x}", opcode);
+                )
+            });
+        if let Some(window_id) = restored {
+            self.focused_window = Some(window_id);
+            self.render_engine.set_focus(Some(window_id));
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_register_taskbar(&mut self, data: &[u8]) -> SysResult<()> {
+        let port =
+            decode::<RegisterTaskbarRequest>(data).and_then(handlers::handle_register_taskbar);
+        if let Some(port) = port {
+            self.taskbar_port = Some(port);
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_notify(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        handlers::handle_notify(&mut self.render_engine, data, now_ms);
+        Ok(())
+    }
+
+    pub(super) fn on_set_opacity(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_opacity(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_input_region(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_input_region(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_opaque_region(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_opaque_region(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_start_capture(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_start_capture(&mut self.render_engine, &mut self.capture_port, data)
+    }
+
+    pub(super) fn on_capture_release(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_capture_release(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_window_scale(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_window_scale(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_transient_for(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_transient_for(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_damage_region(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_damage_region(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_shade_window(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_shade_window(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_preedit(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_preedit(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_constraints(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_constraints(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_fullscreen(&mut self, data: &[u8]) -> SysResult<()> {
+        let changed = handlers::handle_set_fullscreen(&mut self.render_engine, data);
+        self.send_configure(changed);
+        Ok(())
+    }
+
+    pub(super) fn on_set_window_tag(&mut self, data: &[u8]) -> SysResult<()> {
+        let restored = handlers::handle_set_window_tag(
+            &mut self.render_engine,
+            &self.window_memory,
+            self.drag_min_visible,
+            data,
+        );
+        if let Some((window_id, minimized)) = restored {
+            self.send_configure(Some(window_id));
+            if minimized {
+                self.minimize_window(window_id);
             }
         }
+        Ok(())
+    }
+
+    pub(super) fn on_load_window_memory(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_load_window_memory(&mut self.window_memory, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_window_layer(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_window_layer(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_wallpaper_mode(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_wallpaper_mode(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_set_wallpaper_from_window(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_wallpaper_from_window(&mut self.render_engine, data);
+        Ok(())
+    }
 
+    pub(super) fn on_set_position_relative(&mut self, data: &[u8]) -> SysResult<()> {
+        let changed = handlers::handle_set_position_relative(&mut self.render_engine, data);
+        self.send_configure(changed);
         Ok(())
     }
 
+    pub(super) fn on_touch_update(&mut self, data: &[u8]) -> SysResult<()> {
+        self.handle_touch_update(data)
+    }
+
+    pub(super) fn on_get_pixel(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        handlers::handle_get_pixel(
+            &self.render_engine,
+            &mut self.client_ports,
+            &mut self.pending_replies,
+            data,
+            now_ms,
+        );
+        Ok(())
+    }
+
+    pub(super) fn on_activate_window_by_title(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        if let Some(window_id) = handlers::handle_activate_window_by_title(
+            &self.render_engine,
+            &mut self.client_ports,
+            &mut self.pending_replies,
+            data,
+            now_ms,
+        ) {
+            self.activate_window(window_id);
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_get_heartbeat(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        handlers::handle_get_heartbeat(
+            &self.render_engine,
+            &self.heartbeat,
+            &mut self.client_ports,
+            &mut self.pending_replies,
+            data,
+            now_ms,
+        );
+        Ok(())
+    }
+
+    pub(super) fn on_get_stats(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        handlers::handle_get_stats(
+            &self.render_engine,
+            &mut self.client_ports,
+            &mut self.pending_replies,
+            data,
+            now_ms,
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "selftest")]
+    pub(super) fn on_self_test(&mut self, data: &[u8]) -> SysResult<()> {
+        let now_ms = self.now_ms();
+        handlers::handle_self_test(
+            &mut self.render_engine,
+            &mut self.client_ports,
+            &mut self.pending_replies,
+            data,
+            now_ms,
+        );
+        Ok(())
+    }
+
+    pub(super) fn on_request_frame(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_request_frame(&mut self.render_engine, data);
+        Ok(())
+    }
+
+    pub(super) fn on_refresh_window(&mut self, data: &[u8]) -> SysResult<()> {
+        // Reenviar geometria: o cliente pode ter perdido um configure
+        let window_id = handlers::handle_refresh_window(&mut self.render_engine, data);
+        self.send_configure(window_id);
+        Ok(())
+    }
+
+    pub(super) fn on_set_keymap(&mut self, data: &[u8]) -> SysResult<()> {
+        if let Some(keymap) = handlers::handle_set_keymap(data) {
+            self.keymap = keymap;
+        }
+        Ok(())
+    }
+
+    pub(super) fn on_set_compositor_option(&mut self, data: &[u8]) -> SysResult<()> {
+        handlers::handle_set_compositor_option(
+            &mut self.render_engine,
+            &mut self.touch_emulates_mouse,
+            &mut self.button_layout,
+            data,
+        );
+        Ok(())
+    }
+
+    /// Envia o configure da janela cuja geometria mudou (se houver).
+    fn send_configure(&self, window_id: Option<u32>) {
+        let window_id = match window_id {
+            Some(id) => id,
+            None => return,
+        };
+        if let Some(win) = self.render_engine.get_window(window_id) {
+            dispatch_configure_event(&self.client_ports, window_id, win.rect());
+        }
+    }
+
+    /// Minimiza a janela. Se ela tinha o foco, o foco passa para a janela
+    /// visível mais à frente.
+    fn minimize_window(&mut self, window_id: u32) {
+        handlers::handle_minimize_window(
+            &mut self.render_engine,
+            self.taskbar_port.as_ref(),
+            window_id,
+        );
+        if self.focused_window == Some(window_id) {
+            let next = self.render_engine.topmost_focusable_window(window_id);
+            self.focus_window(next);
+        }
+    }
+
+    /// Restaura (se minimizada), traz à frente e foca a janela.
+    fn activate_window(&mut self, window_id: u32) {
+        let minimized = self
+            .render_engine
+            .get_window(window_id)
+            .is_some_and(|win| win.state == WindowState::Minimized);
+        if minimized {
+            handlers::handle_restore_window(
+                &mut self.render_engine,
+                self.taskbar_port.as_ref(),
+                window_id,
+            );
+        } else {
+            self.render_engine.bring_to_front(window_id);
+        }
+        self.focus_window(Some(window_id));
+    }
+
+    /// Define a janela com foco e avisa a taskbar.
+    fn focus_window(&mut self, window_id: Option<u32>) {
+        self.focused_window = window_id;
+        self.render_engine.set_focus(window_id);
+
+        let id = match window_id {
+            Some(id) => id,
+            None => return,
+        };
+        if let Some(win) = self
+            .render_engine
+            .get_window(id)
+            .filter(|win| win.shows_in_taskbar())
+        {
+            let title = win.title.clone();
+            send_lifecycle_event(
+                self.taskbar_port.as_ref(),
+                lifecycle_events::FOCUSED,
+                id,
+                &title,
+            );
+        }
+    }
+
+    /// Destrói a janela e limpa todo o estado do servidor ligado a ela.
+    fn destroy_window(&mut self, window_id: u32) {
+        // Lembrar a geometria de janelas com tag
+        if let Some(win) = self.render_engine.get_window(window_id) {
+            if !win.tag.is_empty() {
+                let rect = match win.restore_rect {
+                    Some(rect) if win.state != WindowState::Normal => rect,
+                    _ => win.rect(),
+                };
+                let entry = RememberedWindow {
+                    tag: win.tag.clone(),
+                    rect,
+                    maximized: win.state == WindowState::Maximized,
+                    minimized: win.state == WindowState::Minimized,
+                };
+                send_window_remembered(&entry);
+                self.window_memory.remember(entry);
+            }
+        }
+        self.pending_replies
+            .retain(|r| r.client_window.map(|(id, _)| id) != Some(window_id));
+        self.touch.forget_window(window_id);
+        self.popups.retain(|&id| id != window_id);
+        if self.press_target == Some(window_id) {
+            self.press_target = None;
+        }
+        if self.drag.window_id == Some(window_id) {
+            self.drag.stop();
+        }
+        if self.keyboard_move.keyboard_move_active && self.keyboard_move.window_id == window_id {
+            self.keyboard_move.stop();
+        }
+        if self.click.last_window == Some(window_id) {
+            self.click.clear();
+        }
+        if self.focused_window == Some(window_id) {
+            self.focused_window = None;
+            self.render_engine.set_focus(None);
+        }
+        handlers::handle_destroy_window(
+            &mut self.render_engine,
+            &mut self.client_ports,
+            self.taskbar_port.as_ref(),
+            window_id,
+        );
+    }
+
     // =========================================================================
     // INPUT
     // =========================================================================
 
     fn handle_input_update(&mut self, data: &[u8]) -> SysResult<()> {
-        if data.len() < core::mem::size_of::<InputUpdateRequest>() {
+        let req = match decode::<InputUpdateRequest>(data) {
+            Some(req) => req,
+            None => return Ok(()),
+        };
+        let now_ms = self.now_ms();
+        self.idle.input(now_ms);
+
+        // Movimento só de posição: fica para o fim do frame
+        if req.event_type == 2
+            && self
+                .input
+                .coalesce_motion(req.mouse_x, req.mouse_y, req.mouse_buttons)
+        {
             return Ok(());
         }
 
-        let req = unsafe { &*(data.as_ptr() as *const InputUpdateRequest) };
-
-        // Atualizar estado interno
-        self.input.update_from_service(
-            req.event_type,
-            req.key_code,
-            req.key_pressed,
-            req.mouse_x,
-            req.mouse_y,
-            req.mouse_buttons,
-        );
+        // Qualquer outro evento vê o ponteiro na posição mais recente
+        self.flush_pending_motion()?;
 
         // Processar teclado
         if req.event_type == 1 {
+            let pressed = req.key_pressed == 1;
+            self.modifiers.update(req.key_code, pressed);
+
+            if self.handle_window_menu_key(req.key_code, pressed) {
+                return Ok(());
+            }
+            if self.handle_keyboard_move(req.key_code, pressed) {
+                return Ok(());
+            }
+
             if let Some(target_id) = self.focused_window {
-                dispatch_key_event(
-                    &self.client_ports,
-                    target_id,
-                    req.key_code,
-                    req.key_pressed == 1,
-                );
+                let ch = self.keymap.translate(req.key_code, self.modifiers.shift);
+                dispatch_key_event(&self.client_ports, target_id, req.key_code, ch, pressed);
             }
         }
 
         // Processar mouse
         if req.event_type == 2 {
-            self.mouse.update(req.mouse_x, req.mouse_y);
+            self.input.move_pointer(req.mouse_x, req.mouse_y);
             self.process_mouse_input(req.mouse_buttons)?;
         }
 
         Ok(())
     }
 
+    /// Processa o movimento de mouse adiado por `coalesce_motion`.
+    fn flush_pending_motion(&mut self) -> SysResult<()> {
+        let motion = match self.input.take_pending_motion() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let buttons = self.input.buttons();
+        self.input.move_pointer(motion.x, motion.y);
+        self.process_mouse_input(buttons)
+    }
+
+    /// Processa um toque: roteia para a janela sob o ponto do DOWN.
+    fn handle_touch_update(&mut self, data: &[u8]) -> SysResult<()> {
+        self.flush_pending_motion()?;
+        let req = match decode::<TouchUpdateRequest>(data) {
+            Some(req) => req,
+            None => return Ok(()),
+        };
+        let now_ms = self.now_ms();
+        self.idle.input(now_ms);
+
+        let window_id = match req.phase {
+            touch_phase::DOWN => {
+                let window_id = self.render_engine.window_at_point(req.x, req.y);
+                self.touch.begin(req.touch_id, window_id);
+                window_id
+            }
+            touch_phase::MOVE | touch_phase::UP => self.touch.window_for(req.touch_id),
+            _ => return Ok(()),
+        };
+
+        if let Some(window_id) = window_id {
+            let (rel_x, rel_y) = self.get_relative_coords(window_id, req.x, req.y);
+            dispatch_touch_event(
+                &self.client_ports,
+                window_id,
+                req.touch_id,
+                req.phase,
+                rel_x,
+                rel_y,
+            );
+        }
+
+        // Toque primário também gera mouse (botão esquerdo)
+        let primary = self.touch.is_primary(req.touch_id);
+        if req.phase == touch_phase::UP {
+            self.touch.end(req.touch_id);
+        }
+        if self.touch_emulates_mouse && primary {
+            let buttons = if req.phase == touch_phase::UP { 0 } else { 1 };
+            self.input.move_pointer(req.x, req.y);
+            self.process_mouse_input(buttons)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processa o modo de mover janela pelo teclado.
+    ///
+    /// Alt+F7 entra no modo para a janela com foco; setas movem (Shift para
+    /// passo maior), Enter confirma e Escape volta à posição original.
+    /// Retorna true se a tecla foi consumida pelo compositor.
+    fn handle_keyboard_move(&mut self, key_code: u32, pressed: bool) -> bool {
+        if !self.keyboard_move.keyboard_move_active {
+            if !(pressed && key_code == scancode::F7 && self.modifiers.alt) {
+                return false;
+            }
+
+            return match self.focused_window {
+                Some(window_id) => self.start_keyboard_move(window_id),
+                None => false,
+            };
+        }
+
+        // Modificadoras continuam chegando ao cliente
+        if ModifierState::is_modifier(key_code) {
+            return false;
+        }
+
+        if !pressed {
+            return true;
+        }
+
+        let window_id = self.keyboard_move.window_id;
+        let step = if self.modifiers.shift {
+            KEYBOARD_MOVE_STEP_LARGE
+        } else {
+            KEYBOARD_MOVE_STEP
+        };
+
+        match key_code {
+            scancode::UP => self.keyboard_move_by(window_id, 0, -step),
+            scancode::DOWN => self.keyboard_move_by(window_id, 0, step),
+            scancode::LEFT => self.keyboard_move_by(window_id, -step, 0),
+            scancode::RIGHT => self.keyboard_move_by(window_id, step, 0),
+            scancode::ENTER => self.keyboard_move.stop(),
+            scancode::ESCAPE => {
+                let (x, y) = (self.keyboard_move.origin_x, self.keyboard_move.origin_y);
+                self.keyboard_move_to(window_id, x, y);
+                self.keyboard_move.stop();
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Entra no modo de mover pelo teclado (apenas janelas normais móveis).
+    fn start_keyboard_move(&mut self, window_id: u32) -> bool {
+        let origin = match self.render_engine.get_window(window_id) {
+            Some(win) if win.layer == LayerType::Normal && win.movable => win.position,
+            _ => return false,
+        };
+
+        self.keyboard_move.start(window_id, origin.x, origin.y);
+        redpowder::println!("[Firefly] Movendo janela {} pelo teclado", window_id);
+        true
+    }
+
+    fn keyboard_move_by(&mut self, window_id: u32, dx: i32, dy: i32) {
+        if let Some(win) = self.render_engine.get_window(window_id) {
+            let x = win.position.x + dx;
+            let y = win.position.y + dy;
+            self.keyboard_move_to(window_id, x, y);
+        } else {
+            // Janela destruída durante o modo
+            self.keyboard_move.stop();
+        }
+    }
+
+    fn keyboard_move_to(&mut self, window_id: u32, x: i32, y: i32) {
+        self.render_engine.move_window(window_id, x, y);
+        if let Some(win) = self.render_engine.get_window(window_id) {
+            dispatch_configure_event(&self.client_ports, window_id, win.rect());
+        }
+    }
+
     fn process_mouse_input(&mut self, buttons: u32) -> SysResult<()> {
-        let x = self.mouse.x;
-        let y = self.mouse.y;
+        let x = self.input.pointer.x;
+        let y = self.input.pointer.y;
+        let grab = self.press_target;
+
+        // Click fora dos popups abertos: fecha os popups e é consumido
+        let pressed =
+            self.input.left_just_pressed(buttons) || self.input.right_just_pressed(buttons);
+        if pressed && self.dismiss_popups_at(x, y) {
+            self.input.save_buttons(buttons);
+            return Ok(());
+        }
+
+        // Click (press); com o menu de janela aberto, o click vai para ele.
+        // Durante um grab, o press vai para a janela do grab (abaixo).
+        if grab.is_none() && self.input.left_just_pressed(buttons) {
+            if self.render_engine.window_menu().is_some() {
+                self.handle_window_menu_click(x, y);
+            } else {
+                self.handle_mouse_click(x, y, buttons)?;
+            }
+        }
 
-        // Click (press)
-        if self.mouse.left_just_pressed(buttons) {
-            self.handle_mouse_click(x, y, buttons)?;
+        // Botão direito na barra de título abre o menu de janela
+        if grab.is_none() && self.input.right_just_pressed(buttons) {
+            self.open_window_menu_at(x, y);
         }
 
         // Drag
         if let Some(win_id) = self.drag.window_id {
-            if self.mouse.left_pressed(buttons) {
-                let new_x = x - self.drag.offset_x;
-                let new_y = y - self.drag.offset_y;
+            if self.input.left_pressed(buttons) {
+                if self.drag.has_moved(x, y) {
+                    self.unmaximize_for_drag(win_id, x, y);
+                }
+                let (new_x, new_y) = self.drag.update(x, y);
+                let (new_x, new_y) = self.constrain_drag(win_id, new_x, new_y);
                 self.render_engine.move_window(win_id, new_x, new_y);
                 self.render_engine.full_screen_damage();
             } else {
@@ -295,39 +970,78 @@ impl Server {
             }
         }
 
-        // Release
-        if self.mouse.left_just_released(buttons) {
-            if let Some(focused) = self.focused_window {
-                let (rel_x, rel_y) = self.get_relative_coords(focused, x, y);
-                dispatch_mouse_event(&self.client_ports, focused, rel_x, rel_y, buttons, false);
+        // Grab implícito: enquanto algum botão estiver pressionado, presses,
+        // releases e movimento vão para a janela do press, mesmo com o
+        // ponteiro fora dela. O grab termina quando todos forem soltos.
+        if let Some(target) = grab {
+            let prev = self.input.buttons();
+            let (rel_x, rel_y) = self.get_relative_coords(target, x, y);
+            if buttons & !prev != 0 {
+                dispatch_mouse_event(&self.client_ports, target, rel_x, rel_y, buttons, true);
+            }
+            if prev & !buttons != 0 {
+                dispatch_mouse_event(&self.client_ports, target, rel_x, rel_y, buttons, false);
+            }
+            if buttons == prev && self.drag.window_id.is_none() {
+                dispatch_pointer_motion(&self.client_ports, target, rel_x, rel_y, buttons);
+            }
+            if buttons == 0 {
+                self.press_target = None;
             }
+        }
+
+        if self.input.left_just_released(buttons) {
             self.drag.stop();
         }
 
-        self.mouse.save_buttons(buttons);
+        self.input.save_buttons(buttons);
         Ok(())
     }
 
+    /// Fecha, do mais novo para o mais antigo, os popups que não contêm
+    /// (x, y). Retorna true se algum foi fechado (o click é consumido).
+    fn dismiss_popups_at(&mut self, x: i32, y: i32) -> bool {
+        let mut dismissed = false;
+        while let Some(&popup) = self.popups.last() {
+            let inside = self
+                .render_engine
+                .get_window(popup)
+                .is_some_and(|win| win.contains_point(x, y));
+            if inside {
+                break;
+            }
+            self.popups.pop();
+            dispatch_popup_dismissed(&self.client_ports, popup);
+            dismissed = true;
+        }
+        dismissed
+    }
+
     fn handle_mouse_click(&mut self, x: i32, y: i32, buttons: u32) -> SysResult<()> {
+        self.press_target = None;
+
+        // Notificações ficam acima de todas as janelas
+        if let Some(toast) = self.render_engine.take_notification_at(x, y) {
+            if let Some(port) = &toast.port {
+                send_notify_clicked(port, toast.id);
+            }
+            return Ok(());
+        }
+
         let window_id = match self.render_engine.window_at_point(x, y) {
             Some(id) => id,
             None => return Ok(()),
         };
 
-        // Atualizar foco
-        if self.focused_window != Some(window_id) {
-            self.focused_window = Some(window_id);
-            self.render_engine.set_focus(Some(window_id));
+        // Painéis recebem o click, mas não tomam o foco nem sobem de z-order
+        let is_panel = self
+            .render_engine
+            .get_window(window_id)
+            .is_some_and(|win| win.layer == LayerType::Panel);
 
-            if let Some(win) = self.render_engine.get_window(window_id) {
-                let title = win.title.clone();
-                send_lifecycle_event(
-                    self.taskbar_port.as_ref(),
-                    lifecycle_events::FOCUSED,
-                    window_id,
-                    &title,
-                );
-            }
+        // Atualizar foco
+        if !is_panel && self.focused_window != Some(window_id) {
+            self.focus_window(Some(window_id));
 
             // Trazer para frente (apenas janelas normais)
             if let Some(win) = self.render_engine.get_window(window_id) {
@@ -340,6 +1054,7 @@ impl Server {
         // Dispatch click
         let (rel_x, rel_y) = self.get_relative_coords(window_id, x, y);
         dispatch_mouse_event(&self.client_ports, window_id, rel_x, rel_y, buttons, true);
+        self.press_target = Some(window_id);
 
         // Verificar click na title bar
         self.handle_titlebar_click(window_id, x, y)?;
@@ -348,64 +1063,45 @@ impl Server {
     }
 
     fn handle_titlebar_click(&mut self, window_id: u32, x: i32, y: i32) -> SysResult<()> {
-        let (rect, has_decorations, layer) = {
+        let (rect, has_decorations, layer, movable) = {
             let win = match self.render_engine.get_window(window_id) {
                 Some(w) => w,
                 None => return Ok(()),
             };
-            (win.rect(), win.has_decorations(), win.layer)
+            (win.rect(), win.has_decorations(), win.layer, win.movable)
         };
 
-        if !has_decorations || layer == LayerType::Background {
+        if !has_decorations || !has_window_chrome(layer) {
             return Ok(());
         }
 
         let rel_x = x - rect.x;
         let rel_y = y - rect.y;
 
-        // Title bar (24px height)
-        if rel_y >= 0 && rel_y < 24 {
-            let w = rect.width as i32;
-            let btn_size = 20;
-            let close_x = w - btn_size - 2;
-            let min_x = w - (btn_size * 2) - 6;
-
-            if rel_x >= close_x && rel_x < close_x + btn_size {
-                // Close
-                if self.focused_window == Some(window_id) {
-                    self.focused_window = None;
-                    self.render_engine.set_focus(None);
-                }
-                handlers::handle_destroy_window(
-                    &mut self.render_engine,
-                    &mut self.client_ports,
-                    self.taskbar_port.as_ref(),
-                    window_id,
-                );
-            } else if rel_x >= min_x && rel_x < min_x + btn_size {
-                // Minimize
-                handlers::handle_minimize_window(
-                    &mut self.render_engine,
-                    self.taskbar_port.as_ref(),
-                    window_id,
-                );
-            } else {
+        if rel_y < 0 || rel_y >= TITLEBAR_HEIGHT as i32 {
+            return Ok(());
+        }
+
+        match self.button_layout.button_at(rect, x, y) {
+            Some(TitlebarButton::Close) => {
+                self.destroy_window(window_id);
+            }
+            Some(TitlebarButton::Minimize) => {
+                self.minimize_window(window_id);
+            }
+            Some(TitlebarButton::Maximize) => {
+                self.toggle_maximize(window_id);
+            }
+            None => {
                 // Title bar drag ou double-click
                 if self.click.is_double_click(window_id, self.frame_count) {
-                    // Maximize/Restore
-                    let screen_size = self.render_engine.size();
-                    if let Some(win) = self.render_engine.get_window_mut(window_id) {
-                        if win.state == gfx_types::window::WindowState::Maximized {
-                            win.restore();
-                        } else {
-                            win.maximize(screen_size);
-                        }
-                        self.render_engine.full_screen_damage();
-                    }
+                    self.toggle_maximize(window_id);
                     self.click.clear();
                 } else {
-                    // Start drag
-                    self.drag.start(window_id, rel_x, rel_y);
+                    // Start drag (janelas com NO_MOVE ignoram o arraste)
+                    if movable {
+                        self.drag.start(window_id, x, y, rel_x, rel_y);
+                    }
                     self.click.register(window_id, self.frame_count);
                 }
             }
@@ -414,6 +1110,159 @@ impl Server {
         Ok(())
     }
 
+    // =========================================================================
+    // MENU DE JANELA
+    // =========================================================================
+
+    /// Abre o menu se (x, y) está na barra de título de uma janela decorada.
+    fn open_window_menu_at(&mut self, x: i32, y: i32) {
+        let window_id = match self.render_engine.window_at_point(x, y) {
+            Some(id) => id,
+            None => return,
+        };
+        let on_titlebar = self.render_engine.get_window(window_id).is_some_and(|win| {
+            let rel_y = y - win.position.y;
+            win.has_decorations()
+                && has_window_chrome(win.layer)
+                && (0..TITLEBAR_HEIGHT as i32).contains(&rel_y)
+        });
+
+        if on_titlebar {
+            let screen = self.render_engine.size();
+            self.render_engine.open_window_menu(WindowMenu::new(
+                window_id,
+                Point::new(x, y),
+                screen,
+            ));
+        }
+    }
+
+    /// Click com o menu aberto: executa o item ou fecha (click fora).
+    fn handle_window_menu_click(&mut self, x: i32, y: i32) {
+        if let Some(menu) = self.render_engine.close_window_menu() {
+            if let Some(item) = menu.item_at(x, y) {
+                self.run_window_menu_item(menu.window_id, item);
+            }
+        }
+    }
+
+    /// Teclas do menu de janela: setas navegam, Enter executa, Escape fecha.
+    /// Retorna true se a tecla foi consumida (qualquer tecla com o menu aberto).
+    fn handle_window_menu_key(&mut self, key_code: u32, pressed: bool) -> bool {
+        if self.render_engine.window_menu().is_none() {
+            return false;
+        }
+        if !pressed {
+            return true;
+        }
+
+        match key_code {
+            scancode::UP => self.render_engine.select_window_menu_item(false),
+            scancode::DOWN => self.render_engine.select_window_menu_item(true),
+            scancode::ENTER => {
+                if let Some(menu) = self.render_engine.close_window_menu() {
+                    self.run_window_menu_item(menu.window_id, menu.selected_item());
+                }
+            }
+            scancode::ESCAPE => {
+                self.render_engine.close_window_menu();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Executa uma ação do menu de janela.
+    fn run_window_menu_item(&mut self, window_id: u32, item: WindowMenuItem) {
+        match item {
+            WindowMenuItem::Minimize => {
+                self.minimize_window(window_id);
+            }
+            WindowMenuItem::Maximize => self.toggle_maximize(window_id),
+            WindowMenuItem::Shade => {
+                let shaded = self
+                    .render_engine
+                    .get_window(window_id)
+                    .is_some_and(|win| win.is_shaded());
+                self.render_engine.set_shaded(window_id, !shaded);
+            }
+            WindowMenuItem::Move => {
+                self.start_keyboard_move(window_id);
+            }
+            WindowMenuItem::Close => self.destroy_window(window_id),
+        }
+    }
+
+    /// Limita a posição de arraste para manter a barra de título na tela.
+    ///
+    /// Janelas maximizadas não são limitadas.
+    fn constrain_drag(&self, window_id: u32, x: i32, y: i32) -> (i32, i32) {
+        let win = match self.render_engine.get_window(window_id) {
+            Some(win) if win.state != WindowState::Maximized => win,
+            _ => return (x, y),
+        };
+
+        let area = self.render_engine.work_area();
+        let pos = keep_titlebar_reachable(
+            Point::new(x, y),
+            win.size,
+            TITLEBAR_HEIGHT,
+            area,
+            self.drag_min_visible,
+        );
+        (pos.x, pos.y)
+    }
+
+    /// Restaura uma janela maximizada no primeiro movimento do arraste.
+    ///
+    /// A barra de título continua sob o ponteiro na mesma proporção da
+    /// largura, e o arraste segue com o tamanho restaurado.
+    fn unmaximize_for_drag(&mut self, window_id: u32, x: i32, y: i32) {
+        let win = match self.render_engine.get_window_mut(window_id) {
+            Some(win) if win.state == WindowState::Maximized => win,
+            _ => return,
+        };
+
+        let maximized = win.rect();
+        win.restore();
+        let rel_x = (x - maximized.x).clamp(0, maximized.width as i32);
+        let offset_x = if maximized.width == 0 {
+            0
+        } else {
+            (rel_x as i64 * win.size.width as i64 / maximized.width as i64) as i32
+        };
+        let offset_y = (y - maximized.y).clamp(0, TITLEBAR_HEIGHT as i32 - 1);
+
+        self.render_engine
+            .move_window(window_id, x - offset_x, y - offset_y);
+        self.render_engine.full_screen_damage();
+        self.drag.start(window_id, x, y, offset_x, offset_y);
+        if let Some(win) = self.render_engine.get_window(window_id) {
+            dispatch_configure_event(&self.client_ports, window_id, win.rect());
+        }
+    }
+
+    /// Maximiza ou restaura a janela.
+    fn toggle_maximize(&mut self, window_id: u32) {
+        let area = self.render_engine.work_area();
+        if let Some(win) = self.render_engine.get_window_mut(window_id) {
+            if !win.resizable {
+                return;
+            }
+            if win.state == WindowState::Maximized {
+                win.restore();
+            } else {
+                win.maximize(area);
+            }
+            self.render_engine.full_screen_damage();
+        }
+    }
+
+    /// Tempo desde o início do compositor (ms), derivado dos frames.
+    fn now_ms(&self) -> u64 {
+        self.frame_count * FRAME_INTERVAL_MS
+    }
+
     fn get_relative_coords(&self, window_id: u32, x: i32, y: i32) -> (i32, i32) {
         if let Some(win) = self.render_engine.get_window(window_id) {
             let local = win.to_local(x, y);
@@ -423,3 +1272,60 @@ impl Server {
         }
     }
 }
+
+// =============================================================================
+// AUXILIARES
+// =============================================================================
+
+/// Camadas com barra de título interativa (arraste, botões, menu de janela).
+///
+/// Background e Panel só recebem os eventos de mouse: nunca são arrastadas
+/// nem manipuladas pela barra de título.
+fn has_window_chrome(layer: LayerType) -> bool {
+    !matches!(layer, LayerType::Background | LayerType::Panel)
+}
+
+/// Executa `f` até `attempts` vezes, esperando `backoff_ms` (dobrando a cada
+/// falha) entre as tentativas. Retorna o erro da última tentativa.
+fn retry_with_backoff<T>(
+    attempts: u32,
+    backoff_ms: u64,
+    mut f: impl FnMut() -> SysResult<T>,
+) -> SysResult<T> {
+    let mut delay = backoff_ms;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(e) => {
+                redpowder::println!(
+                    "[Firefly] Tentativa {}/{} falhou ({:?}), repetindo em {} ms",
+                    attempt,
+                    attempts,
+                    e,
+                    delay
+                );
+                let _ = redpowder::time::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Lê o modo atual do framebuffer como `DisplayInfo`.
+///
+/// `get_info` não informa o formato de pixel (só largura, altura e stride em
+/// pixels), então o formato é sempre ARGB8888.
+fn query_display_info() -> SysResult<DisplayInfo> {
+    let fb_info = get_info()?;
+    Ok(DisplayInfo {
+        id: 0,
+        width: fb_info.width,
+        height: fb_info.height,
+        refresh_rate_mhz: 60_000,
+        format: gfx_types::color::PixelFormat::ARGB8888,
+        stride: fb_info.stride * 4,
+    })
+}
@@ -2,6 +2,12 @@
 //!
 //! Estado do servidor (foco, drag, etc).
 
+use alloc::string::String;
+use alloc::vec::Vec;
+use gfx_types::geometry::{Rect, Size};
+
+use crate::input::scancode;
+
 /// Estado de arraste de janela.
 #[derive(Default)]
 pub struct DragState {
@@ -11,6 +17,10 @@ pub struct DragState {
     pub offset_x: i32,
     /// Offset Y do arraste.
     pub offset_y: i32,
+    /// Última posição X do ponteiro.
+    last_x: i32,
+    /// Última posição Y do ponteiro.
+    last_y: i32,
 }
 
 impl DragState {
@@ -18,10 +28,25 @@ impl DragState {
         Self::default()
     }
 
-    pub fn start(&mut self, window_id: u32, offset_x: i32, offset_y: i32) {
+    /// Inicia o arraste com o ponteiro em (x, y), a `offset` da origem da janela.
+    pub fn start(&mut self, window_id: u32, x: i32, y: i32, offset_x: i32, offset_y: i32) {
         self.window_id = Some(window_id);
         self.offset_x = offset_x;
         self.offset_y = offset_y;
+        self.last_x = x;
+        self.last_y = y;
+    }
+
+    /// Move o ponteiro para (x, y) e retorna a nova posição da janela.
+    pub fn update(&mut self, x: i32, y: i32) -> (i32, i32) {
+        self.last_x = x;
+        self.last_y = y;
+        (x - self.offset_x, y - self.offset_y)
+    }
+
+    /// Retorna true se o ponteiro saiu da última posição.
+    pub fn has_moved(&self, x: i32, y: i32) -> bool {
+        x != self.last_x || y != self.last_y
     }
 
     pub fn stop(&mut self) {
@@ -66,47 +91,257 @@ impl ClickState {
     }
 }
 
-/// Estado do mouse.
+/// Estado das teclas modificadoras.
+#[derive(Default)]
+pub struct ModifierState {
+    /// Shift pressionado.
+    pub shift: bool,
+    /// Ctrl pressionado.
+    pub ctrl: bool,
+    /// Alt pressionado.
+    pub alt: bool,
+}
+
+impl ModifierState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Atualiza a partir de um evento de tecla.
+    pub fn update(&mut self, key_code: u32, pressed: bool) {
+        match key_code {
+            scancode::LEFT_SHIFT | scancode::RIGHT_SHIFT => self.shift = pressed,
+            scancode::LEFT_CTRL => self.ctrl = pressed,
+            scancode::LEFT_ALT => self.alt = pressed,
+            _ => {}
+        }
+    }
+
+    /// Retorna true se a tecla é uma modificadora.
+    pub fn is_modifier(key_code: u32) -> bool {
+        matches!(
+            key_code,
+            scancode::LEFT_SHIFT | scancode::RIGHT_SHIFT | scancode::LEFT_CTRL | scancode::LEFT_ALT
+        )
+    }
+}
+
+/// Estado do modo de mover janela pelo teclado.
+#[derive(Default)]
+pub struct KeyboardMoveState {
+    /// Modo ativo.
+    pub keyboard_move_active: bool,
+    /// Janela sendo movida.
+    pub window_id: u32,
+    /// Posição X original (para cancelar).
+    pub origin_x: i32,
+    /// Posição Y original (para cancelar).
+    pub origin_y: i32,
+}
+
+impl KeyboardMoveState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&mut self, window_id: u32, origin_x: i32, origin_y: i32) {
+        self.keyboard_move_active = true;
+        self.window_id = window_id;
+        self.origin_x = origin_x;
+        self.origin_y = origin_y;
+    }
+
+    pub fn stop(&mut self) {
+        self.keyboard_move_active = false;
+    }
+}
+
+/// Limites de recursos por cliente (identificado pela porta de resposta).
+pub struct ClientQuota {
+    /// Máximo de janelas simultâneas.
+    pub max_windows: usize,
+    /// Máximo de bytes de SHM somando todas as janelas.
+    pub max_shm_bytes: usize,
+    /// Dimensão máxima de uma superfície, em múltiplos da tela (por eixo).
+    pub max_surface_scale: u32,
+}
+
+impl ClientQuota {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retorna true se o cliente pode alocar mais uma janela de `shm_bytes`.
+    pub fn allows(&self, window_count: usize, used_bytes: usize, shm_bytes: usize) -> bool {
+        window_count < self.max_windows
+            && used_bytes.saturating_add(shm_bytes) <= self.max_shm_bytes
+    }
+
+    /// Retorna true se uma superfície `width`x`height` cabe no limite
+    /// relativo à tela `display`.
+    pub fn surface_fits(&self, width: u32, height: u32, display: Size) -> bool {
+        let scale = self.max_surface_scale as u64;
+        width as u64 <= display.width as u64 * scale
+            && height as u64 <= display.height as u64 * scale
+    }
+}
+
+impl Default for ClientQuota {
+    fn default() -> Self {
+        Self {
+            max_windows: 32,
+            max_shm_bytes: 128 * 1024 * 1024,
+            max_surface_scale: 2,
+        }
+    }
+}
+
+/// Resposta aguardando a porta do cliente ficar disponível.
+///
+/// O cliente cria sua porta de resposta em paralelo ao CREATE_WINDOW; em
+/// vez de bloquear o loop principal, a conexão é tentada a cada iteração
+/// até `deadline_ms`.
+pub struct PendingReply {
+    /// Nome da porta de resposta.
+    pub port_name: String,
+    /// Mensagem a enviar.
+    pub message: Vec<u8>,
+    /// Janela (e bytes de SHM) a registrar em `client_ports` ao conectar.
+    pub client_window: Option<(u32, usize)>,
+    /// Desistir após este instante (ms).
+    pub deadline_ms: u64,
+}
+
+/// Heartbeat do loop principal.
+///
+/// Avança a cada frame que chega inteiro ao display. Um supervisor consulta
+/// via GET_HEARTBEAT: sem resposta, o loop travou; `frames` parado enquanto
+/// a tela deveria mudar indica que o present está falhando. Ocioso (sem
+/// damage), `frames` não avança.
+pub struct Heartbeat {
+    /// Frames apresentados por inteiro.
+    pub frames: u64,
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self { frames: 0 }
+    }
+
+    /// Registra um frame apresentado por inteiro.
+    pub fn beat(&mut self) {
+        self.frames += 1;
+    }
+}
+
+/// Máximo de tags lembradas (as mais antigas são descartadas).
+const MAX_REMEMBERED_WINDOWS: usize = 32;
+
+/// Geometria lembrada de uma janela com tag.
+#[derive(Clone, Debug)]
+pub struct RememberedWindow {
+    /// Tag da janela.
+    pub tag: String,
+    /// Geometria normal (fora da maximização/minimização).
+    pub rect: Rect,
+    /// Janela estava maximizada.
+    pub maximized: bool,
+    /// Janela estava minimizada.
+    pub minimized: bool,
+}
+
+/// Geometria/estado das janelas com tag, por tag.
+///
+/// Gravado quando a janela é destruída e aplicado quando uma nova janela
+/// recebe a mesma tag. Cada gravação também vai para o serviço de sessão
+/// ([`SESSION_PORT`](super::protocol::SESSION_PORT)), que a devolve quando
+/// o compositor reinicia.
+#[derive(Default)]
+pub struct WindowMemory {
+    entries: Vec<RememberedWindow>,
+}
+
+impl WindowMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grava a geometria de `tag`, substituindo a anterior.
+    pub fn remember(&mut self, entry: RememberedWindow) {
+        self.entries.retain(|e| e.tag != entry.tag);
+        if self.entries.len() >= MAX_REMEMBERED_WINDOWS {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+    }
+
+    /// Geometria lembrada para `tag`.
+    pub fn lookup(&self, tag: &str) -> Option<&RememberedWindow> {
+        self.entries.iter().find(|e| e.tag == tag)
+    }
+}
+
+/// Toque ativo (dedo encostado).
+pub struct ActiveTouch {
+    /// ID do toque (do serviço de input).
+    pub touch_id: u32,
+    /// Janela que recebeu o DOWN; MOVE/UP vão para ela.
+    pub window_id: Option<u32>,
+}
+
+/// Estado dos toques ativos.
 #[derive(Default)]
-pub struct MouseState {
-    /// Posição X.
-    pub x: i32,
-    /// Posição Y.
-    pub y: i32,
-    /// Botões no frame anterior.
-    pub prev_buttons: u32,
+pub struct TouchState {
+    /// Toques encostados.
+    pub touches: Vec<ActiveTouch>,
+    /// Toque primário (emulação de mouse): o primeiro sem outros ativos.
+    pub primary: Option<u32>,
 }
 
-impl MouseState {
+impl TouchState {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn update(&mut self, x: i32, y: i32) {
-        self.x = x;
-        self.y = y;
+    /// Registra um novo toque.
+    pub fn begin(&mut self, touch_id: u32, window_id: Option<u32>) {
+        self.end(touch_id);
+        if self.touches.is_empty() {
+            self.primary = Some(touch_id);
+        }
+        self.touches.push(ActiveTouch {
+            touch_id,
+            window_id,
+        });
     }
 
-    pub fn save_buttons(&mut self, buttons: u32) {
-        self.prev_buttons = buttons;
+    /// Janela que recebe os eventos do toque.
+    pub fn window_for(&self, touch_id: u32) -> Option<u32> {
+        self.touches
+            .iter()
+            .find(|t| t.touch_id == touch_id)
+            .and_then(|t| t.window_id)
     }
 
-    /// Retorna true se botão esquerdo foi pressionado neste frame.
-    pub fn left_just_pressed(&self, current_buttons: u32) -> bool {
-        let left_now = (current_buttons & 0x01) != 0;
-        let left_was = (self.prev_buttons & 0x01) != 0;
-        left_now && !left_was
+    /// Remove um toque.
+    pub fn end(&mut self, touch_id: u32) {
+        self.touches.retain(|t| t.touch_id != touch_id);
+        if self.primary == Some(touch_id) {
+            self.primary = None;
+        }
     }
 
-    /// Retorna true se botão esquerdo foi solto neste frame.
-    pub fn left_just_released(&self, current_buttons: u32) -> bool {
-        let left_now = (current_buttons & 0x01) != 0;
-        let left_was = (self.prev_buttons & 0x01) != 0;
-        !left_now && left_was
+    /// Retorna true se o toque é o primário.
+    pub fn is_primary(&self, touch_id: u32) -> bool {
+        self.primary == Some(touch_id)
     }
 
-    /// Retorna true se botão esquerdo está pressionado.
-    pub fn left_pressed(&self, current_buttons: u32) -> bool {
-        (current_buttons & 0x01) != 0
+    /// Esquece uma janela destruída.
+    pub fn forget_window(&mut self, window_id: u32) {
+        for touch in self.touches.iter_mut() {
+            if touch.window_id == Some(window_id) {
+                touch.window_id = None;
+            }
+        }
     }
 }
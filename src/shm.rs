@@ -0,0 +1,35 @@
+//! # SHM
+//!
+//! Acesso verificado aos pixels de uma [`SharedMemory`].
+//!
+//! Todo slice de pixels sobre SHM passa por aqui: o tamanho pedido é
+//! conferido contra o mapeamento, e um mapeamento nulo ou menor que o
+//! esperado retorna `None` em vez de ler ou escrever fora da região.
+
+use redpowder::ipc::SharedMemory;
+
+/// Verifica se `len` pixels ARGB32 cabem no mapeamento.
+#[inline]
+fn fits(shm: &SharedMemory, len: usize) -> bool {
+    !shm.as_ptr().is_null() && len.checked_mul(4).is_some_and(|bytes| bytes <= shm.size())
+}
+
+/// Primeiros `len` pixels da SHM, ou `None` se não estiver mapeada ou
+/// for menor que `len * 4` bytes.
+///
+/// O cliente pode escrever no buffer concorrentemente; a composição tolera
+/// ler um frame parcialmente atualizado.
+pub fn as_u32_slice(shm: &SharedMemory, len: usize) -> Option<&[u32]> {
+    if !fits(shm, len) {
+        return None;
+    }
+    Some(unsafe { core::slice::from_raw_parts(shm.as_ptr() as *const u32, len) })
+}
+
+/// Versão mutável de [`as_u32_slice`].
+pub fn as_u32_slice_mut(shm: &mut SharedMemory, len: usize) -> Option<&mut [u32]> {
+    if !fits(shm, len) {
+        return None;
+    }
+    Some(unsafe { core::slice::from_raw_parts_mut(shm.as_mut_ptr() as *mut u32, len) })
+}
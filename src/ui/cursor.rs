@@ -3,7 +3,10 @@
 //! Desenho do cursor do mouse.
 
 use gfx_types::color::Color;
-use gfx_types::geometry::Size;
+use gfx_types::geometry::{Rect, Size};
+
+use crate::render::Blitter;
+use crate::scene::damage::rect_contains;
 
 // =============================================================================
 // CONSTANTES
@@ -46,44 +49,41 @@ const CURSOR_OUTLINE: Color = Color::BLACK;
 /// Cor do preenchimento do cursor.
 const CURSOR_FILL: Color = Color::WHITE;
 
+/// Cor da sombra projetada (preto a 50%, misturado com o fundo).
+const CURSOR_SHADOW: Color = Color(0x80000000);
+
+/// Deslocamento da sombra em relação à seta (pixels, para baixo e à direita).
+const SHADOW_OFFSET: i32 = 1;
+
 // =============================================================================
 // FUNÇÕES
 // =============================================================================
 
-/// Desenha o cursor na posição especificada.
-pub fn draw(buffer: &mut [u32], buffer_size: Size, x: i32, y: i32) {
-    let stride = buffer_size.width as usize;
-
-    for py in 0..CURSOR_HEIGHT {
-        let screen_y = y as usize + py;
-        if screen_y >= buffer_size.height as usize {
-            continue;
-        }
-
-        for px in 0..CURSOR_WIDTH {
-            let screen_x = x as usize + px;
-            if screen_x >= buffer_size.width as usize {
-                continue;
-            }
-
-            let pixel_type = CURSOR_BITMAP[py][px];
-            if pixel_type == 0 {
-                continue; // Transparente
-            }
+/// Retorna o retângulo ocupado pelo cursor (incluindo a sombra) na posição
+/// especificada.
+#[inline]
+pub fn bounds(x: i32, y: i32) -> Rect {
+    Rect::new(
+        x,
+        y,
+        CURSOR_WIDTH as u32 + SHADOW_OFFSET as u32,
+        CURSOR_HEIGHT as u32 + SHADOW_OFFSET as u32,
+    )
+}
 
-            let idx = screen_y * stride + screen_x;
-            if idx < buffer.len() {
-                buffer[idx] = match pixel_type {
-                    1 => CURSOR_OUTLINE.as_u32(),
-                    2 => CURSOR_FILL.as_u32(),
-                    _ => continue,
-                };
-            }
-        }
-    }
+/// Desenha o cursor na posição especificada, só dentro de `clip`.
+pub fn draw(buffer: &mut [u32], buffer_size: Size, x: i32, y: i32, clip: Rect) {
+    draw_colored(buffer, buffer_size, x, y, CURSOR_OUTLINE, CURSOR_FILL, clip);
 }
 
 /// Desenha cursor com cor customizada.
+///
+/// A sombra é a silhueta da seta deslocada e misturada com o fundo; a seta
+/// é desenhada opaca por cima. O hotspot continua em `(x, y)`.
+///
+/// Só pixels dentro de `clip` são tocados: fora do damage o backbuffer
+/// ainda tem o cursor do frame anterior, e misturar a sombra de novo
+/// escureceria o fundo a cada frame.
 pub fn draw_colored(
     buffer: &mut [u32],
     buffer_size: Size,
@@ -91,33 +91,30 @@ pub fn draw_colored(
     y: i32,
     outline: Color,
     fill: Color,
+    clip: Rect,
 ) {
-    let stride = buffer_size.width as usize;
-
-    for py in 0..CURSOR_HEIGHT {
-        let screen_y = y as usize + py;
-        if screen_y >= buffer_size.height as usize {
-            continue;
-        }
-
-        for px in 0..CURSOR_WIDTH {
-            let screen_x = x as usize + px;
-            if screen_x >= buffer_size.width as usize {
-                continue;
-            }
-
-            let pixel_type = CURSOR_BITMAP[py][px];
-            if pixel_type == 0 {
-                continue;
+    // Sombra
+    for (py, row) in CURSOR_BITMAP.iter().enumerate() {
+        for (px, &pixel_type) in row.iter().enumerate() {
+            let sx = x + px as i32 + SHADOW_OFFSET;
+            let sy = y + py as i32 + SHADOW_OFFSET;
+            if pixel_type != 0 && rect_contains(clip, sx, sy) {
+                Blitter::put_pixel_blend(buffer, buffer_size, sx, sy, CURSOR_SHADOW);
             }
+        }
+    }
 
-            let idx = screen_y * stride + screen_x;
-            if idx < buffer.len() {
-                buffer[idx] = match pixel_type {
-                    1 => outline.as_u32(),
-                    2 => fill.as_u32(),
-                    _ => continue,
-                };
+    // Seta
+    for (py, row) in CURSOR_BITMAP.iter().enumerate() {
+        for (px, &pixel_type) in row.iter().enumerate() {
+            let color = match pixel_type {
+                1 => outline,
+                2 => fill,
+                _ => continue, // Transparente
+            };
+            let (cx, cy) = (x + px as i32, y + py as i32);
+            if rect_contains(clip, cx, cy) {
+                Blitter::put_pixel(buffer, buffer_size, cx, cy, color);
             }
         }
     }
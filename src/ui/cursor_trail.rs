@@ -0,0 +1,99 @@
+//! # Cursor Trail
+//!
+//! Rastro do cursor para diagnóstico de latência de input (feature
+//! `cursor-trail`).
+//!
+//! Mantém as últimas posições amostradas a cada frame e as desenha como
+//! pontos que desbotam, mostrando quão suave é a amostragem do input em
+//! relação à renderização.
+
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use crate::render::Blitter;
+use crate::scene::clamp_rect;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Número de posições mantidas no rastro.
+pub const TRAIL_LEN: usize = 8;
+
+/// Tamanho de cada ponto do rastro.
+const DOT_SIZE: u32 = 3;
+
+/// Alpha máximo (ponto mais recente).
+const DOT_MAX_ALPHA: u32 = 0xC0;
+
+/// Cor base dos pontos (alpha é calculado por idade).
+const DOT_COLOR: u32 = 0x00FF4040;
+
+// =============================================================================
+// CURSOR TRAIL
+// =============================================================================
+
+/// Ring buffer das posições recentes do cursor.
+pub struct CursorTrail {
+    /// Posições (circular).
+    positions: [Point; TRAIL_LEN],
+    /// Próximo índice de escrita.
+    head: usize,
+    /// Quantidade de posições válidas.
+    len: usize,
+}
+
+impl CursorTrail {
+    /// Cria rastro vazio.
+    pub fn new() -> Self {
+        Self {
+            positions: [Point::ZERO; TRAIL_LEN],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Registra uma nova posição, descartando a mais antiga se cheio.
+    pub fn push(&mut self, pos: Point) {
+        self.positions[self.head] = pos;
+        self.head = (self.head + 1) % TRAIL_LEN;
+        self.len = (self.len + 1).min(TRAIL_LEN);
+    }
+
+    /// Retorna se o rastro está cheio e todo sobre `pos` (cursor parado).
+    pub fn is_settled(&self, pos: Point) -> bool {
+        self.len == TRAIL_LEN && self.iter().all(|p| p.x == pos.x && p.y == pos.y)
+    }
+
+    /// Itera da posição mais antiga para a mais recente.
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        let start = (self.head + TRAIL_LEN - self.len) % TRAIL_LEN;
+        (0..self.len).map(move |i| self.positions[(start + i) % TRAIL_LEN])
+    }
+
+    /// Retorna o retângulo de um ponto do rastro.
+    pub fn dot_rect(pos: Point) -> Rect {
+        let half = (DOT_SIZE / 2) as i32;
+        Rect::new(pos.x - half, pos.y - half, DOT_SIZE, DOT_SIZE)
+    }
+
+    /// Desenha o rastro, limitado a `clip`.
+    pub fn draw(&self, buffer: &mut [u32], buffer_size: Size, clip: Rect) {
+        for (age, pos) in self.iter().enumerate() {
+            let rect = match clamp_rect(Self::dot_rect(pos), clip) {
+                Some(r) => r,
+                None => continue,
+            };
+
+            let alpha = DOT_MAX_ALPHA * (age as u32 + 1) / TRAIL_LEN as u32;
+            let color = Color((alpha << 24) | DOT_COLOR);
+            Blitter::fill_rect_blend(buffer, buffer_size, rect, color);
+        }
+    }
+}
+
+impl Default for CursorTrail {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Desenho de decorações de janelas (título, botões).
 
+use alloc::vec;
+use alloc::vec::Vec;
 use gfx_types::color::Color;
 use gfx_types::geometry::{Rect, Size};
 
@@ -12,15 +14,23 @@ use crate::render::Blitter;
 // =============================================================================
 
 /// Altura da barra de título.
-// TODO: Revisar no futuro
-#[allow(unused)]
 pub const TITLEBAR_HEIGHT: u32 = 24;
 
 // TODO: Revisar no futuro
 #[allow(unused)]
 /// Largura da borda.
+///
+/// Única fonte para o desenho da borda e para a zona de redimensionamento.
 pub const BORDER_WIDTH: u32 = 1;
 
+/// Área extra (além da borda) que ainda conta como borda para redimensionar.
+const RESIZE_GRAB_MARGIN: u32 = 3;
+
+// TODO: Revisar no futuro
+#[allow(unused)]
+/// Largura da zona de redimensionamento (cresce junto com a borda).
+pub const RESIZE_ZONE_WIDTH: u32 = BORDER_WIDTH + RESIZE_GRAB_MARGIN;
+
 // TODO: Revisar no futuro
 #[allow(unused)]
 /// Cor da barra de título (ativa).
@@ -46,11 +56,21 @@ pub const BORDER_COLOR_INACTIVE: Color = Color(0xFF3d3d3d);
 /// Cor do texto.
 pub const TEXT_COLOR: Color = Color::WHITE;
 
-// TODO: Revisar no futuro
-#[allow(unused)]
+/// Espessura do anel de foco.
+pub const FOCUS_RING_WIDTH: u32 = 2;
+
+/// Cor padrão do anel de foco (quando a janela não define `border_color`).
+pub const FOCUS_RING_COLOR: Color = Color::REDSTONE_ACCENT;
+
 /// Tamanho dos botões.
 pub const BTN_SIZE: u32 = 20;
 
+/// Margem entre o botão mais externo e a borda da janela.
+const BTN_MARGIN: i32 = 2;
+
+/// Espaço entre botões.
+const BTN_SPACING: i32 = 4;
+
 // TODO: Revisar no futuro
 #[allow(unused)]
 /// Cor do botão fechar.
@@ -61,6 +81,136 @@ pub const BTN_CLOSE_COLOR: Color = Color::REDSTONE_ACCENT;
 /// Cor do botão minimizar.
 pub const BTN_MINIMIZE_COLOR: Color = Color(0xFF4a90d9);
 
+// TODO: Revisar no futuro
+#[allow(unused)]
+/// Cor do botão maximizar.
+pub const BTN_MAXIMIZE_COLOR: Color = Color(0xFF5cb85c);
+
+// =============================================================================
+// LAYOUT DOS BOTÕES
+// =============================================================================
+
+/// Botão da barra de título.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitlebarButton {
+    Close,
+    Minimize,
+    Maximize,
+}
+
+/// Lado da barra de título onde ficam os botões.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonSide {
+    Left,
+    Right,
+}
+
+/// Disposição dos botões da barra de título.
+///
+/// Define o hit-testing dos botões. Configurável via `SET_COMPOSITOR_OPTION`
+/// (`compositor_option::BUTTON_LAYOUT`), para concordar com a barra que o
+/// cliente desenha.
+pub struct ButtonLayout {
+    /// Lado dos botões.
+    pub side: ButtonSide,
+    /// Botões, do mais externo (junto à borda) para o mais interno.
+    pub order: Vec<TitlebarButton>,
+}
+
+impl ButtonLayout {
+    /// Retorna o retângulo de cada botão para a janela dada.
+    pub fn button_rects(
+        &self,
+        window_rect: Rect,
+    ) -> impl Iterator<Item = (TitlebarButton, Rect)> + '_ {
+        let step = BTN_SIZE as i32 + BTN_SPACING;
+        let y = window_rect.y + BTN_MARGIN;
+
+        self.order.iter().enumerate().map(move |(i, &button)| {
+            let offset = BTN_MARGIN + i as i32 * step;
+            let x = match self.side {
+                ButtonSide::Left => window_rect.x + offset,
+                ButtonSide::Right => window_rect.right() - offset - BTN_SIZE as i32,
+            };
+            (button, Rect::new(x, y, BTN_SIZE, BTN_SIZE))
+        })
+    }
+
+    /// Retorna o botão sob o ponto (coordenadas globais).
+    ///
+    /// A área clicável ocupa a altura inteira da barra de título.
+    pub fn button_at(&self, window_rect: Rect, x: i32, y: i32) -> Option<TitlebarButton> {
+        if y < window_rect.y || y >= window_rect.y + TITLEBAR_HEIGHT as i32 {
+            return None;
+        }
+
+        self.button_rects(window_rect)
+            .find(|(_, rect)| x >= rect.x && x < rect.right())
+            .map(|(button, _)| button)
+    }
+}
+
+impl Default for ButtonLayout {
+    fn default() -> Self {
+        Self {
+            side: ButtonSide::Right,
+            order: vec![TitlebarButton::Close, TitlebarButton::Minimize],
+        }
+    }
+}
+
+// =============================================================================
+// REDIMENSIONAMENTO
+// =============================================================================
+
+// TODO: Revisar no futuro
+#[allow(unused)]
+/// Borda (ou canto) da janela usada para redimensionar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// TODO: Revisar no futuro
+#[allow(unused)]
+/// Retorna a borda de redimensionamento sob o ponto (coordenadas globais).
+///
+/// A zona fica dentro do retângulo da janela e tem `RESIZE_ZONE_WIDTH`
+/// de largura.
+pub fn resize_edge_at(window_rect: Rect, x: i32, y: i32) -> Option<ResizeEdge> {
+    if x < window_rect.x || x >= window_rect.right() {
+        return None;
+    }
+    if y < window_rect.y || y >= window_rect.bottom() {
+        return None;
+    }
+
+    let zone = RESIZE_ZONE_WIDTH as i32;
+    let left = x < window_rect.x + zone;
+    let right = x >= window_rect.right() - zone;
+    let top = y < window_rect.y + zone;
+    let bottom = y >= window_rect.bottom() - zone;
+
+    match (top, bottom, left, right) {
+        (true, _, true, _) => Some(ResizeEdge::TopLeft),
+        (true, _, _, true) => Some(ResizeEdge::TopRight),
+        (_, true, true, _) => Some(ResizeEdge::BottomLeft),
+        (_, true, _, true) => Some(ResizeEdge::BottomRight),
+        (true, _, _, _) => Some(ResizeEdge::Top),
+        (_, true, _, _) => Some(ResizeEdge::Bottom),
+        (_, _, true, _) => Some(ResizeEdge::Left),
+        (_, _, _, true) => Some(ResizeEdge::Right),
+        _ => None,
+    }
+}
+
 // =============================================================================
 // FUNÇÕES
 // =============================================================================
@@ -74,6 +224,7 @@ pub fn draw_window_decoration(
     window_rect: Rect,
     title: &str,
     is_focused: bool,
+    layout: &ButtonLayout,
 ) {
     let titlebar_color = if is_focused {
         TITLEBAR_COLOR_ACTIVE
@@ -99,18 +250,53 @@ pub fn draw_window_decoration(
     // 2. Borda
     Blitter::stroke_rect(buffer, buffer_size, window_rect, BORDER_WIDTH, border_color);
 
-    // 3. Botão fechar (X)
-    let close_x = window_rect.right() - BTN_SIZE as i32 - 2;
-    let close_y = window_rect.y + 2;
-    let close_rect = Rect::new(close_x, close_y, BTN_SIZE, BTN_SIZE);
-    Blitter::fill_rect(buffer, buffer_size, close_rect, BTN_CLOSE_COLOR);
-    draw_close_icon(buffer, buffer_size, close_x + 4, close_y + 4);
-
-    // 4. Botão minimizar (-)
-    let min_x = close_x - BTN_SIZE as i32 - 4;
-    let min_rect = Rect::new(min_x, close_y, BTN_SIZE, BTN_SIZE);
-    Blitter::fill_rect(buffer, buffer_size, min_rect, BTN_MINIMIZE_COLOR);
-    draw_minimize_icon(buffer, buffer_size, min_x + 4, close_y + 8);
+    // 3. Anel de foco
+    if is_focused {
+        draw_focus_ring(
+            buffer,
+            buffer_size,
+            window_rect,
+            FOCUS_RING_COLOR,
+            Rect::from_size(buffer_size),
+        );
+    }
+
+    // 4. Botões
+    for (button, rect) in layout.button_rects(window_rect) {
+        match button {
+            TitlebarButton::Close => {
+                Blitter::fill_rect(buffer, buffer_size, rect, BTN_CLOSE_COLOR);
+                draw_close_icon(buffer, buffer_size, rect.x + 4, rect.y + 4);
+            }
+            TitlebarButton::Minimize => {
+                Blitter::fill_rect(buffer, buffer_size, rect, BTN_MINIMIZE_COLOR);
+                draw_minimize_icon(buffer, buffer_size, rect.x + 4, rect.y + 8);
+            }
+            TitlebarButton::Maximize => {
+                Blitter::fill_rect(buffer, buffer_size, rect, BTN_MAXIMIZE_COLOR);
+                draw_maximize_icon(buffer, buffer_size, rect.x + 4, rect.y + 4);
+            }
+        }
+    }
+}
+
+/// Desenha o anel de foco em volta da área de conteúdo (abaixo da barra
+/// de título), limitado a `clip`.
+pub fn draw_focus_ring(
+    buffer: &mut [u32],
+    buffer_size: Size,
+    window_rect: Rect,
+    color: Color,
+    clip: Rect,
+) {
+    let content = Rect::new(
+        window_rect.x,
+        window_rect.y + TITLEBAR_HEIGHT as i32,
+        window_rect.width,
+        window_rect.height.saturating_sub(TITLEBAR_HEIGHT),
+    );
+
+    Blitter::stroke_rect_clipped(buffer, buffer_size, content, FOCUS_RING_WIDTH, clip, color);
 }
 
 /// Desenha ícone X (fechar).
@@ -122,6 +308,12 @@ fn draw_close_icon(buffer: &mut [u32], size: Size, x: i32, y: i32) {
     }
 }
 
+/// Desenha ícone □ (maximizar).
+fn draw_maximize_icon(buffer: &mut [u32], size: Size, x: i32, y: i32) {
+    let color = Color::WHITE;
+    Blitter::stroke_rect(buffer, size, Rect::new(x, y, 12, 12), 2, color);
+}
+
 /// Desenha ícone - (minimizar).
 fn draw_minimize_icon(buffer: &mut [u32], size: Size, x: i32, y: i32) {
     let color = Color::WHITE;
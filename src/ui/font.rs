@@ -0,0 +1,119 @@
+//! # Bitmap Font
+//!
+//! Fonte bitmap 5x7 (ASCII imprimível) para texto desenhado pelo compositor.
+
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Largura de um glifo.
+pub const GLYPH_WIDTH: u32 = 5;
+
+/// Altura de um glifo.
+pub const GLYPH_HEIGHT: u32 = 7;
+
+/// Avanço horizontal por caractere (glifo + espaçamento).
+pub const CHAR_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+/// Altura de linha (glifo + espaçamento).
+pub const LINE_HEIGHT: u32 = GLYPH_HEIGHT + 2;
+
+/// Primeiro caractere da tabela.
+const FIRST_CHAR: u8 = 0x20;
+
+/// Glifos 0x20..=0x7E, em colunas (bit 0 = linha superior).
+#[rustfmt::skip]
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00,0x00,0x00,0x00,0x00], [0x00,0x00,0x5F,0x00,0x00], [0x00,0x07,0x00,0x07,0x00], [0x14,0x7F,0x14,0x7F,0x14], // ' ' ! " #
+    [0x24,0x2A,0x7F,0x2A,0x12], [0x23,0x13,0x08,0x64,0x62], [0x36,0x49,0x55,0x22,0x50], [0x00,0x05,0x03,0x00,0x00], // $ % & '
+    [0x00,0x1C,0x22,0x41,0x00], [0x00,0x41,0x22,0x1C,0x00], [0x14,0x08,0x3E,0x08,0x14], [0x08,0x08,0x3E,0x08,0x08], // ( ) * +
+    [0x00,0x50,0x30,0x00,0x00], [0x08,0x08,0x08,0x08,0x08], [0x00,0x60,0x60,0x00,0x00], [0x20,0x10,0x08,0x04,0x02], // , - . /
+    [0x3E,0x51,0x49,0x45,0x3E], [0x00,0x42,0x7F,0x40,0x00], [0x42,0x61,0x51,0x49,0x46], [0x21,0x41,0x45,0x4B,0x31], // 0 1 2 3
+    [0x18,0x14,0x12,0x7F,0x10], [0x27,0x45,0x45,0x45,0x39], [0x3C,0x4A,0x49,0x49,0x30], [0x01,0x71,0x09,0x05,0x03], // 4 5 6 7
+    [0x36,0x49,0x49,0x49,0x36], [0x06,0x49,0x49,0x29,0x1E], [0x00,0x36,0x36,0x00,0x00], [0x00,0x56,0x36,0x00,0x00], // 8 9 : ;
+    [0x08,0x14,0x22,0x41,0x00], [0x14,0x14,0x14,0x14,0x14], [0x00,0x41,0x22,0x14,0x08], [0x02,0x01,0x51,0x09,0x06], // < = > ?
+    [0x32,0x49,0x79,0x41,0x3E], [0x7E,0x11,0x11,0x11,0x7E], [0x7F,0x49,0x49,0x49,0x36], [0x3E,0x41,0x41,0x41,0x22], // @ A B C
+    [0x7F,0x41,0x41,0x22,0x1C], [0x7F,0x49,0x49,0x49,0x41], [0x7F,0x09,0x09,0x09,0x01], [0x3E,0x41,0x49,0x49,0x7A], // D E F G
+    [0x7F,0x08,0x08,0x08,0x7F], [0x00,0x41,0x7F,0x41,0x00], [0x20,0x40,0x41,0x3F,0x01], [0x7F,0x08,0x14,0x22,0x41], // H I J K
+    [0x7F,0x40,0x40,0x40,0x40], [0x7F,0x02,0x0C,0x02,0x7F], [0x7F,0x04,0x08,0x10,0x7F], [0x3E,0x41,0x41,0x41,0x3E], // L M N O
+    [0x7F,0x09,0x09,0x09,0x06], [0x3E,0x41,0x51,0x21,0x5E], [0x7F,0x09,0x19,0x29,0x46], [0x46,0x49,0x49,0x49,0x31], // P Q R S
+    [0x01,0x01,0x7F,0x01,0x01], [0x3F,0x40,0x40,0x40,0x3F], [0x1F,0x20,0x40,0x20,0x1F], [0x3F,0x40,0x38,0x40,0x3F], // T U V W
+    [0x63,0x14,0x08,0x14,0x63], [0x07,0x08,0x70,0x08,0x07], [0x61,0x51,0x49,0x45,0x43], [0x00,0x7F,0x41,0x41,0x00], // X Y Z [
+    [0x02,0x04,0x08,0x10,0x20], [0x00,0x41,0x41,0x7F,0x00], [0x04,0x02,0x01,0x02,0x04], [0x40,0x40,0x40,0x40,0x40], // \ ] ^ _
+    [0x00,0x01,0x02,0x04,0x00], [0x20,0x54,0x54,0x54,0x78], [0x7F,0x48,0x44,0x44,0x38], [0x38,0x44,0x44,0x44,0x20], // ` a b c
+    [0x38,0x44,0x44,0x48,0x7F], [0x38,0x54,0x54,0x54,0x18], [0x08,0x7E,0x09,0x01,0x02], [0x0C,0x52,0x52,0x52,0x3E], // d e f g
+    [0x7F,0x08,0x04,0x04,0x78], [0x00,0x44,0x7D,0x40,0x00], [0x20,0x40,0x44,0x3D,0x00], [0x7F,0x10,0x28,0x44,0x00], // h i j k
+    [0x00,0x41,0x7F,0x40,0x00], [0x7C,0x04,0x18,0x04,0x78], [0x7C,0x08,0x04,0x04,0x78], [0x38,0x44,0x44,0x44,0x38], // l m n o
+    [0x7C,0x14,0x14,0x14,0x08], [0x08,0x14,0x14,0x18,0x7C], [0x7C,0x08,0x04,0x04,0x08], [0x48,0x54,0x54,0x54,0x20], // p q r s
+    [0x04,0x3F,0x44,0x40,0x20], [0x3C,0x40,0x40,0x20,0x7C], [0x1C,0x20,0x40,0x20,0x1C], [0x3C,0x40,0x30,0x40,0x3C], // t u v w
+    [0x44,0x28,0x10,0x28,0x44], [0x0C,0x50,0x50,0x50,0x3C], [0x44,0x64,0x54,0x4C,0x44], [0x00,0x08,0x36,0x41,0x00], // x y z {
+    [0x00,0x00,0x7F,0x00,0x00], [0x00,0x41,0x36,0x08,0x00], [0x08,0x04,0x08,0x10,0x08],                             // | } ~
+];
+
+// =============================================================================
+// FUNÇÕES
+// =============================================================================
+
+/// Desenha um texto de uma linha, limitado a `clip`.
+///
+/// Caracteres fora do ASCII imprimível são desenhados como `?`.
+pub fn draw_text(
+    buffer: &mut [u32],
+    buffer_size: Size,
+    origin: Point,
+    text: &str,
+    color: Color,
+    clip: Rect,
+) {
+    let mut x = origin.x;
+    for ch in text.chars() {
+        draw_char(buffer, buffer_size, x, origin.y, ch, color, clip);
+        x += CHAR_ADVANCE as i32;
+    }
+}
+
+/// Desenha um caractere, limitado a `clip`.
+fn draw_char(
+    buffer: &mut [u32],
+    buffer_size: Size,
+    x: i32,
+    y: i32,
+    ch: char,
+    color: Color,
+    clip: Rect,
+) {
+    let code = if (' '..='~').contains(&ch) {
+        ch as u8
+    } else {
+        b'?'
+    };
+    let glyph = &GLYPHS[(code - FIRST_CHAR) as usize];
+    let stride = buffer_size.width as usize;
+    let color_u32 = color.as_u32();
+
+    for (col, bits) in glyph.iter().enumerate() {
+        for row in 0..GLYPH_HEIGHT {
+            if bits & (1 << row) == 0 {
+                continue;
+            }
+
+            let px = x + col as i32;
+            let py = y + row as i32;
+            if px < clip.x || py < clip.y || px >= clip.right() || py >= clip.bottom() {
+                continue;
+            }
+            if px < 0 || py < 0 || px >= buffer_size.width as i32 || py >= buffer_size.height as i32
+            {
+                continue;
+            }
+
+            let idx = py as usize * stride + px as usize;
+            if idx < buffer.len() {
+                buffer[idx] = color_u32;
+            }
+        }
+    }
+}
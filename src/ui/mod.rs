@@ -3,7 +3,14 @@
 //! Componentes de interface do compositor.
 
 pub mod cursor;
+#[cfg(feature = "cursor-trail")]
+pub mod cursor_trail;
 pub mod decoration;
+pub mod font;
+pub mod notification;
+pub mod preedit;
+pub mod splash;
+pub mod window_menu;
 
 // TODO: Revisar no futuro
 #[allow(unused)]
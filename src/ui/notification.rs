@@ -0,0 +1,224 @@
+//! # Notifications
+//!
+//! Notificações (toasts) desenhadas pelo compositor no canto da tela.
+//!
+//! Toasts são empilhados a partir do canto superior direito, expiram após a
+//! duração pedida e deslizam para fora da tela nos últimos instantes.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+use redpowder::ipc::Port;
+
+use super::font;
+use crate::render::easing::Easing;
+use crate::render::Blitter;
+use crate::scene::clamp_rect;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Largura de um toast.
+pub const TOAST_WIDTH: u32 = 240;
+
+/// Altura de um toast.
+pub const TOAST_HEIGHT: u32 = 44;
+
+/// Margem em relação à borda da tela.
+const TOAST_MARGIN: i32 = 12;
+
+/// Espaçamento vertical entre toasts.
+const TOAST_SPACING: i32 = 8;
+
+/// Padding interno do texto.
+const TOAST_PADDING: i32 = 10;
+
+/// Máximo de toasts simultâneos (o mais antigo é descartado).
+const MAX_TOASTS: usize = 5;
+
+/// Duração da animação de saída (ms).
+const SLIDE_OUT_MS: u64 = 250;
+
+/// Cor de fundo do toast.
+const TOAST_BACKGROUND: Color = Color(0xF0303030);
+
+/// Cor da borda do toast.
+const TOAST_BORDER: Color = Color::REDSTONE_ACCENT;
+
+/// Cor do título.
+const TITLE_COLOR: Color = Color::WHITE;
+
+/// Cor do corpo.
+const BODY_COLOR: Color = Color(0xFFC0C0C0);
+
+// =============================================================================
+// TOAST
+// =============================================================================
+
+/// Uma notificação ativa.
+pub struct Toast {
+    /// ID definido pelo cliente (ecoado no evento de click).
+    pub id: u32,
+    /// Título.
+    pub title: String,
+    /// Corpo.
+    pub body: String,
+    /// Instante de expiração (ms).
+    pub expires_at: u64,
+    /// Porta do cliente que criou a notificação.
+    pub port: Option<Port>,
+}
+
+// =============================================================================
+// NOTIFICATION MANAGER
+// =============================================================================
+
+/// Gerenciador de notificações.
+pub struct NotificationManager {
+    /// Toasts ativos (do mais antigo para o mais novo, de cima para baixo).
+    toasts: Vec<Toast>,
+    /// Curva da animação de saída.
+    slide_easing: Easing,
+}
+
+impl NotificationManager {
+    /// Cria novo gerenciador.
+    pub fn new() -> Self {
+        Self {
+            toasts: Vec::new(),
+            slide_easing: Easing::EaseInOut,
+        }
+    }
+
+    // TODO: Revisar no futuro
+    #[allow(unused)]
+    /// Define a curva da animação de saída.
+    pub fn set_slide_easing(&mut self, easing: Easing) {
+        self.slide_easing = easing;
+    }
+
+    /// Retorna se não há toasts ativos.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// Adiciona um toast ao fim da pilha.
+    pub fn push(&mut self, toast: Toast) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(toast);
+    }
+
+    /// Remove toasts expirados.
+    ///
+    /// Retorna true se a aparência da pilha mudou (remoção ou animação).
+    pub fn tick(&mut self, now_ms: u64) -> bool {
+        let before = self.toasts.len();
+        self.toasts.retain(|t| t.expires_at > now_ms);
+
+        let sliding = self
+            .toasts
+            .iter()
+            .any(|t| t.expires_at - now_ms < SLIDE_OUT_MS);
+
+        sliding || self.toasts.len() != before
+    }
+
+    /// Área que pode ser ocupada pela pilha (inclui o trajeto de saída).
+    pub fn bounds(screen: Size) -> Rect {
+        let height = MAX_TOASTS as i32 * (TOAST_HEIGHT as i32 + TOAST_SPACING);
+        let x = screen.width as i32 - TOAST_WIDTH as i32 - TOAST_MARGIN;
+        Rect::new(
+            x,
+            TOAST_MARGIN,
+            TOAST_WIDTH + TOAST_MARGIN as u32,
+            height as u32,
+        )
+    }
+
+    /// Retorna o retângulo do toast no índice dado.
+    fn toast_rect(&self, index: usize, screen: Size, now_ms: u64) -> Rect {
+        let toast = &self.toasts[index];
+        let remaining = toast.expires_at.saturating_sub(now_ms);
+
+        // Deslizar para a direita nos últimos SLIDE_OUT_MS
+        let slide = if remaining < SLIDE_OUT_MS {
+            let travel = TOAST_WIDTH as u64 + TOAST_MARGIN as u64;
+            let eased = self
+                .slide_easing
+                .progress(SLIDE_OUT_MS - remaining, SLIDE_OUT_MS);
+            eased.mul_int(travel as i64) as i32
+        } else {
+            0
+        };
+
+        let x = screen.width as i32 - TOAST_WIDTH as i32 - TOAST_MARGIN + slide;
+        let y = TOAST_MARGIN + index as i32 * (TOAST_HEIGHT as i32 + TOAST_SPACING);
+        Rect::new(x, y, TOAST_WIDTH, TOAST_HEIGHT)
+    }
+
+    /// Remove e retorna o toast no ponto dado (se houver).
+    pub fn take_at_point(&mut self, x: i32, y: i32, screen: Size, now_ms: u64) -> Option<Toast> {
+        let index = (0..self.toasts.len()).find(|&i| {
+            self.toast_rect(i, screen, now_ms)
+                .contains_point(Point::new(x, y))
+        })?;
+        Some(self.toasts.remove(index))
+    }
+
+    /// Desenha todos os toasts, limitado a `clip`.
+    pub fn draw(&self, buffer: &mut [u32], buffer_size: Size, clip: Rect, now_ms: u64) {
+        for (index, toast) in self.toasts.iter().enumerate() {
+            let rect = self.toast_rect(index, buffer_size, now_ms);
+            let visible = match clamp_rect(rect, clip) {
+                Some(r) => r,
+                None => continue,
+            };
+
+            Blitter::fill_rect_blend(buffer, buffer_size, visible, TOAST_BACKGROUND);
+            Blitter::stroke_rect_clipped(buffer, buffer_size, rect, 1, clip, TOAST_BORDER);
+
+            let text_x = rect.x + TOAST_PADDING;
+            let max_chars = ((TOAST_WIDTH as i32 - TOAST_PADDING * 2) / font::CHAR_ADVANCE as i32)
+                .max(0) as usize;
+
+            font::draw_text(
+                buffer,
+                buffer_size,
+                Point::new(text_x, rect.y + TOAST_PADDING),
+                truncate_chars(&toast.title, max_chars),
+                TITLE_COLOR,
+                clip,
+            );
+            font::draw_text(
+                buffer,
+                buffer_size,
+                Point::new(
+                    text_x,
+                    rect.y + TOAST_PADDING + font::LINE_HEIGHT as i32 + 4,
+                ),
+                truncate_chars(&toast.body, max_chars),
+                BODY_COLOR,
+                clip,
+            );
+        }
+    }
+}
+
+impl Default for NotificationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Retorna o prefixo de `text` com no máximo `max` caracteres.
+fn truncate_chars(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
+}
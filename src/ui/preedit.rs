@@ -0,0 +1,94 @@
+//! # Preedit
+//!
+//! Texto de composição do método de entrada (IME), desenhado pelo
+//! compositor perto do cursor de texto da janela, sublinhado, até o
+//! cliente confirmar ou cancelar a composição.
+//!
+//! A fonte bitmap cobre apenas ASCII: outros caracteres aparecem como `?`.
+
+use alloc::string::String;
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use super::font;
+use crate::render::Blitter;
+use crate::scene::clamp_rect;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Padding em volta do texto.
+const PADDING: i32 = 2;
+
+/// Cor de fundo da caixa de composição.
+const BACKGROUND: Color = Color(0xF0202020);
+
+/// Cor do texto e do sublinhado.
+const TEXT_COLOR: Color = Color::WHITE;
+
+// =============================================================================
+// PREEDIT
+// =============================================================================
+
+/// Composição ativa de uma janela.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preedit {
+    /// Janela dona da composição.
+    pub window_id: u32,
+    /// Texto em composição.
+    pub text: String,
+    /// Canto superior esquerdo do texto (coordenadas de tela).
+    pub position: Point,
+}
+
+impl Preedit {
+    /// Cria a composição com o texto em `position` (coordenadas de tela).
+    pub fn new(window_id: u32, text: String, position: Point) -> Self {
+        Self {
+            window_id,
+            text,
+            position,
+        }
+    }
+
+    /// Retângulo ocupado (texto, sublinhado e padding).
+    pub fn rect(&self) -> Rect {
+        let chars = self.text.chars().count() as u32;
+        Rect::new(
+            self.position.x - PADDING,
+            self.position.y - PADDING,
+            chars * font::CHAR_ADVANCE + PADDING as u32 * 2,
+            font::LINE_HEIGHT + PADDING as u32 * 2,
+        )
+    }
+
+    /// Desenha a composição, limitada a `clip`.
+    pub fn draw(&self, buffer: &mut [u32], buffer_size: Size, clip: Rect) {
+        let rect = self.rect();
+        let visible = match clamp_rect(rect, clip) {
+            Some(r) => r,
+            None => return,
+        };
+
+        Blitter::fill_rect_blend(buffer, buffer_size, visible, BACKGROUND);
+        font::draw_text(
+            buffer,
+            buffer_size,
+            self.position,
+            &self.text,
+            TEXT_COLOR,
+            clip,
+        );
+
+        let underline = Rect::new(
+            self.position.x,
+            self.position.y + font::GLYPH_HEIGHT as i32 + 1,
+            self.text.chars().count() as u32 * font::CHAR_ADVANCE,
+            1,
+        );
+        if let Some(r) = clamp_rect(underline, clip) {
+            Blitter::fill_rect(buffer, buffer_size, r, TEXT_COLOR);
+        }
+    }
+}
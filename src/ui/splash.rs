@@ -0,0 +1,131 @@
+//! # Splash
+//!
+//! Tela de abertura exibida entre o início do compositor e a primeira
+//! janela de cliente.
+
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use super::font;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Texto principal.
+const SPLASH_TITLE: &str = "Redstone OS";
+
+/// Texto secundário.
+const SPLASH_SUBTITLE: &str = "Iniciando...";
+
+/// Cor do texto principal.
+const TITLE_COLOR: Color = Color::WHITE;
+
+/// Cor do texto secundário.
+const SUBTITLE_COLOR: Color = Color(0xFFA0A0A0);
+
+/// Duração do fade-out após a primeira janela (ms).
+const SPLASH_FADE_MS: u64 = 300;
+
+// =============================================================================
+// ESTADO
+// =============================================================================
+
+/// Estado da tela de abertura.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplashState {
+    /// Nenhuma janela ainda: splash visível.
+    Showing,
+    /// Primeira janela criada: desaparecendo desde `start_ms`.
+    FadingOut { start_ms: u64 },
+    /// Splash encerrado (não volta mais).
+    Hidden,
+}
+
+impl SplashState {
+    /// Inicia o fade-out (apenas a partir de `Showing`).
+    pub fn dismiss(&mut self, now_ms: u64) {
+        if *self == Self::Showing {
+            *self = Self::FadingOut { start_ms: now_ms };
+        }
+    }
+
+    /// Encerra o fade-out quando o tempo acabar.
+    ///
+    /// Retorna true enquanto o splash está desaparecendo (precisa de redesenho).
+    pub fn update(&mut self, now_ms: u64) -> bool {
+        match *self {
+            Self::FadingOut { start_ms } => {
+                if now_ms.saturating_sub(start_ms) >= SPLASH_FADE_MS {
+                    *self = Self::Hidden;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Opacidade atual do splash (0-255).
+    pub fn opacity(&self, now_ms: u64) -> u8 {
+        match *self {
+            Self::Showing => u8::MAX,
+            Self::FadingOut { start_ms } => {
+                let elapsed = now_ms.saturating_sub(start_ms).min(SPLASH_FADE_MS);
+                (255 - 255 * elapsed / SPLASH_FADE_MS) as u8
+            }
+            Self::Hidden => 0,
+        }
+    }
+}
+
+// =============================================================================
+// DESENHO
+// =============================================================================
+
+/// Desenha o splash centralizado sobre `background`, limitado a `clip`.
+pub fn draw(buffer: &mut [u32], buffer_size: Size, clip: Rect, background: Color, opacity: u8) {
+    if opacity == 0 {
+        return;
+    }
+
+    let center_x = buffer_size.width as i32 / 2;
+    let center_y = buffer_size.height as i32 / 2;
+    let lines = [
+        (
+            SPLASH_TITLE,
+            TITLE_COLOR,
+            center_y - font::LINE_HEIGHT as i32,
+        ),
+        (
+            SPLASH_SUBTITLE,
+            SUBTITLE_COLOR,
+            center_y + font::LINE_HEIGHT as i32,
+        ),
+    ];
+
+    for (text, color, y) in lines {
+        let width = text.len() as i32 * font::CHAR_ADVANCE as i32;
+        font::draw_text(
+            buffer,
+            buffer_size,
+            Point::new(center_x - width / 2, y),
+            text,
+            mix(color, background, opacity),
+            clip,
+        );
+    }
+}
+
+/// Mistura `fg` sobre `bg` com a opacidade dada (resultado opaco).
+fn mix(fg: Color, bg: Color, opacity: u8) -> Color {
+    let a = opacity as u32;
+    let (fg, bg) = (fg.as_u32(), bg.as_u32());
+
+    let channel = |shift: u32| {
+        let f = (fg >> shift) & 0xFF;
+        let b = (bg >> shift) & 0xFF;
+        ((f * a + b * (255 - a)) / 255) << shift
+    };
+
+    Color(0xFF000000 | channel(16) | channel(8) | channel(0))
+}
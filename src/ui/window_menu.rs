@@ -0,0 +1,181 @@
+//! # Window Menu
+//!
+//! Menu de janela aberto com o botão direito na barra de título.
+//!
+//! Navegável pelo mouse (click no item, click fora fecha) e pelo teclado
+//! (setas, Enter, Escape). O compositor desenha o menu acima das janelas.
+
+use gfx_types::color::Color;
+use gfx_types::geometry::{Point, Rect, Size};
+
+use super::font;
+use crate::render::Blitter;
+use crate::scene::clamp_rect;
+
+// =============================================================================
+// CONSTANTES
+// =============================================================================
+
+/// Largura do menu.
+const MENU_WIDTH: u32 = 120;
+
+/// Altura de um item.
+const ITEM_HEIGHT: u32 = 18;
+
+/// Padding horizontal do texto.
+const ITEM_PADDING: i32 = 8;
+
+/// Cor de fundo do menu.
+const MENU_BACKGROUND: Color = Color(0xF0303030);
+
+/// Cor da borda do menu.
+const MENU_BORDER: Color = Color(0xFF505050);
+
+/// Cor de fundo do item selecionado.
+const SELECTED_BACKGROUND: Color = Color::REDSTONE_ACCENT;
+
+/// Cor do texto.
+const TEXT_COLOR: Color = Color::WHITE;
+
+// =============================================================================
+// ITENS
+// =============================================================================
+
+/// Ação de um item do menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowMenuItem {
+    /// Minimizar a janela.
+    Minimize,
+    /// Maximizar ou restaurar.
+    Maximize,
+    /// Enrolar até a barra de título ou desenrolar.
+    Shade,
+    /// Mover pelo teclado (mesmo modo de Alt+F7).
+    Move,
+    /// Fechar a janela.
+    Close,
+}
+
+impl WindowMenuItem {
+    /// Itens na ordem de exibição.
+    pub const ALL: [Self; 5] = [
+        Self::Minimize,
+        Self::Maximize,
+        Self::Shade,
+        Self::Move,
+        Self::Close,
+    ];
+
+    /// Texto do item.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Minimize => "Minimizar",
+            Self::Maximize => "Maximizar",
+            Self::Shade => "Enrolar",
+            Self::Move => "Mover",
+            Self::Close => "Fechar",
+        }
+    }
+}
+
+// =============================================================================
+// WINDOW MENU
+// =============================================================================
+
+/// Menu aberto para uma janela.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowMenu {
+    /// Janela alvo das ações.
+    pub window_id: u32,
+    /// Canto superior esquerdo do menu.
+    pub position: Point,
+    /// Item selecionado (índice em [`WindowMenuItem::ALL`]).
+    pub selected: usize,
+}
+
+impl WindowMenu {
+    /// Abre o menu em `at`, empurrado para dentro da tela.
+    pub fn new(window_id: u32, at: Point, screen: Size) -> Self {
+        let height = Self::height();
+        let max_x = (screen.width as i32 - MENU_WIDTH as i32).max(0);
+        let max_y = (screen.height as i32 - height as i32).max(0);
+        Self {
+            window_id,
+            position: Point::new(at.x.clamp(0, max_x), at.y.clamp(0, max_y)),
+            selected: 0,
+        }
+    }
+
+    /// Altura total do menu.
+    fn height() -> u32 {
+        ITEM_HEIGHT * WindowMenuItem::ALL.len() as u32
+    }
+
+    /// Retângulo ocupado pelo menu.
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.position.x, self.position.y, MENU_WIDTH, Self::height())
+    }
+
+    /// Item sob o ponto (coordenadas de tela).
+    pub fn item_at(&self, x: i32, y: i32) -> Option<WindowMenuItem> {
+        let rect = self.rect();
+        if x < rect.x || x >= rect.x + rect.width as i32 || y < rect.y {
+            return None;
+        }
+        let index = ((y - rect.y) / ITEM_HEIGHT as i32) as usize;
+        WindowMenuItem::ALL.get(index).copied()
+    }
+
+    /// Item selecionado.
+    pub fn selected_item(&self) -> WindowMenuItem {
+        WindowMenuItem::ALL[self.selected]
+    }
+
+    /// Seleciona o próximo item (circular).
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % WindowMenuItem::ALL.len();
+    }
+
+    /// Seleciona o item anterior (circular).
+    pub fn select_prev(&mut self) {
+        let len = WindowMenuItem::ALL.len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    /// Desenha o menu, limitado a `clip`.
+    pub fn draw(&self, buffer: &mut [u32], buffer_size: Size, clip: Rect) {
+        let rect = self.rect();
+        let visible = match clamp_rect(rect, clip) {
+            Some(r) => r,
+            None => return,
+        };
+
+        Blitter::fill_rect_blend(buffer, buffer_size, visible, MENU_BACKGROUND);
+
+        for (index, item) in WindowMenuItem::ALL.iter().enumerate() {
+            let item_rect = Rect::new(
+                rect.x,
+                rect.y + (index as u32 * ITEM_HEIGHT) as i32,
+                MENU_WIDTH,
+                ITEM_HEIGHT,
+            );
+            if index == self.selected {
+                if let Some(selected) = clamp_rect(item_rect, clip) {
+                    Blitter::fill_rect(buffer, buffer_size, selected, SELECTED_BACKGROUND);
+                }
+            }
+
+            let text_y = item_rect.y + (ITEM_HEIGHT - font::GLYPH_HEIGHT) as i32 / 2;
+            font::draw_text(
+                buffer,
+                buffer_size,
+                Point::new(item_rect.x + ITEM_PADDING, text_y),
+                item.label(),
+                TEXT_COLOR,
+                clip,
+            );
+        }
+
+        Blitter::stroke_rect_clipped(buffer, buffer_size, rect, 1, clip, MENU_BORDER);
+    }
+}